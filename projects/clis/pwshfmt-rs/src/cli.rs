@@ -1,8 +1,10 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 use serde::Serialize;
 
+use crate::config::{CaseStyle, Encoding, IndentStyle, LineEnding, RuleName, UnsafePolicy};
 use crate::summary::RunMode;
 
 #[derive(Debug, Clone, Parser)]
@@ -74,12 +76,208 @@ pub struct Cli {
 
     #[arg(long, global = true, value_name = "FILE", help = "严格回退脚本路径")]
     pub fallback_script: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "POLICY",
+        help = "检测到不安全语法时的处理方式"
+    )]
+    pub unsafe_policy: Option<UnsafePolicy>,
+
+    #[arg(
+        long = "casing-dictionary",
+        global = true,
+        value_name = "FILE",
+        action = clap::ArgAction::Append,
+        help = "追加的大小写字典文件（TOML/JSON），可重复传入"
+    )]
+    pub casing_dictionaries: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_name = "BOOL",
+        help = "预扫描待处理文件中的函数定义，按定义修正调用处大小写"
+    )]
+    pub learn_workspace_functions: Option<bool>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "STYLE",
+        help = "关键字大小写风格（默认 lower），即 [rules] keyword_case.style"
+    )]
+    pub keyword_case: Option<CaseStyle>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "STYLE",
+        help = "类型加速器大小写风格，如 [string] / [String]（默认 lower），即 [rules] type_case.style"
+    )]
+    pub type_accelerator_case: Option<CaseStyle>,
+
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_name = "BOOL",
+        help = "将命令位置上的内置别名展开为完整命令名（如 gci、%、?），即 [rules] alias_expansion.enabled"
+    )]
+    pub expand_aliases: Option<bool>,
+
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_name = "BOOL",
+        help = "同时展开仅 Windows 存在的别名（如 ls、cat），需配合 --expand-aliases，即 [rules] alias_expansion.platform"
+    )]
+    pub expand_platform_aliases: Option<bool>,
+
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_name = "BOOL",
+        help = "启用原生排版规则：OTBS 大括号、缩进、运算符空格与赋值对齐，即 [rules] layout.enabled"
+    )]
+    pub layout: Option<bool>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "STYLE",
+        help = "缩进字符（默认 space）"
+    )]
+    pub indent_style: Option<IndentStyle>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        help = "每级缩进的空格数（默认 4，indent_style = tab 时忽略）"
+    )]
+    pub indent_width: Option<usize>,
+
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_name = "BOOL",
+        help = "对齐多行哈希表中的 = 号（默认 true）"
+    )]
+    pub align_assignments: Option<bool>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "MODE",
+        help = "换行符：lf / crlf / auto（按文件中占多数者统一）/ preserve（默认）"
+    )]
+    pub line_ending: Option<LineEnding>,
+
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_name = "BOOL",
+        help = "文件末尾缺少换行时补齐"
+    )]
+    pub insert_final_newline: Option<bool>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "ENCODING",
+        help = "写回编码：preserve（默认，沿用原编码）/ utf8 / utf8-bom / utf16-le / utf16-be"
+    )]
+    pub encoding: Option<Encoding>,
+
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        value_name = "BOOL",
+        help = "含非 ASCII 字符的 .ps1/.psm1/.psd1 必须带 UTF-8 BOM（兼容 Windows PowerShell 5.1）"
+    )]
+    pub bom_for_non_ascii: Option<bool>,
+
+    #[arg(
+        long = "enable-rule",
+        global = true,
+        value_enum,
+        value_name = "RULE",
+        action = clap::ArgAction::Append,
+        help = "开启 [rules] 中的规则，可重复传入"
+    )]
+    pub enable_rules: Vec<RuleName>,
+
+    #[arg(
+        long = "disable-rule",
+        global = true,
+        value_enum,
+        value_name = "RULE",
+        action = clap::ArgAction::Append,
+        help = "关闭 [rules] 中的规则，可重复传入；与 --enable-rule 冲突时以关闭为准"
+    )]
+    pub disable_rules: Vec<RuleName>,
 }
 
 #[derive(Debug, Clone, Subcommand)]
 pub enum Commands {
     Check,
     Write,
+    #[command(subcommand, about = "维护大小写字典文件")]
+    Dict(DictCommand),
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum DictCommand {
+    #[command(about = "从 Get-Command / Get-Help 的 JSON 转储生成规范化字典")]
+    Import {
+        #[arg(value_name = "DUMP_JSON")]
+        dump: PathBuf,
+        #[arg(
+            long,
+            short,
+            value_name = "FILE",
+            help = "输出字典路径（.toml/.json），缺省时输出 TOML 到 stdout"
+        )]
+        output: Option<PathBuf>,
+    },
+    #[command(about = "合并多个字典文件，后出现的写法优先")]
+    Merge {
+        #[arg(value_name = "FILE", required = true)]
+        inputs: Vec<PathBuf>,
+        #[arg(
+            long,
+            short,
+            value_name = "FILE",
+            help = "输出字典路径（.toml/.json），缺省时输出 TOML 到 stdout"
+        )]
+        output: Option<PathBuf>,
+    },
+    #[command(about = "查看命令或参数在当前生效字典中的规范写法")]
+    Show {
+        #[arg(value_name = "NAME", allow_hyphen_values = true)]
+        name: String,
+    },
 }
 
 #[derive(Debug, Clone, Default, Serialize)]
@@ -96,14 +294,74 @@ pub struct CliOverrides {
     pub strict_fallback: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fallback_script: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsafe_policy: Option<UnsafePolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub casing_dictionaries: Option<Vec<PathBuf>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub learn_workspace_functions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indent_style: Option<IndentStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indent_width: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub align_assignments: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_ending: Option<LineEnding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_final_newline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<Encoding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bom_for_non_ascii: Option<bool>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rules: BTreeMap<RuleName, RuleOverride>,
+}
+
+// 只序列化传入的项，其余开关与选项仍来自配置文件与环境变量。
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct RuleOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<CaseStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<bool>,
 }
 
 impl Cli {
-    pub fn run_mode(&self) -> RunMode {
+    pub fn run_mode(&self) -> Option<RunMode> {
         match self.command {
-            Commands::Check => RunMode::Check,
-            Commands::Write => RunMode::Write,
+            Commands::Check => Some(RunMode::Check),
+            Commands::Write => Some(RunMode::Write),
+            Commands::Dict(_) => None,
+        }
+    }
+
+    // `--layout`、`--keyword-case` 等是 `[rules]` 对应项的简写；与 `--enable-rule`/`--disable-rule` 冲突时以后者为准。
+    fn rule_overrides(&self) -> BTreeMap<RuleName, RuleOverride> {
+        let mut rules: BTreeMap<RuleName, RuleOverride> = BTreeMap::new();
+        if self.keyword_case.is_some() {
+            rules.entry(RuleName::KeywordCase).or_default().style = self.keyword_case;
+        }
+        if self.type_accelerator_case.is_some() {
+            rules.entry(RuleName::TypeCase).or_default().style = self.type_accelerator_case;
+        }
+        if self.expand_aliases.is_some() || self.expand_platform_aliases.is_some() {
+            let alias = rules.entry(RuleName::AliasExpansion).or_default();
+            alias.enabled = self.expand_aliases;
+            alias.platform = self.expand_platform_aliases;
+        }
+        if self.layout.is_some() {
+            rules.entry(RuleName::Layout).or_default().enabled = self.layout;
+        }
+
+        let enabled = self.enable_rules.iter().map(|name| (*name, true));
+        let disabled = self.disable_rules.iter().map(|name| (*name, false));
+        for (name, enabled) in enabled.chain(disabled) {
+            rules.entry(name).or_default().enabled = Some(enabled);
         }
+        rules
     }
 
     pub fn overrides(&self) -> CliOverrides {
@@ -114,6 +372,18 @@ impl Cli {
             recurse: self.recurse,
             strict_fallback: self.strict_fallback,
             fallback_script: self.fallback_script.clone(),
+            unsafe_policy: self.unsafe_policy,
+            casing_dictionaries: (!self.casing_dictionaries.is_empty())
+                .then_some(self.casing_dictionaries.clone()),
+            learn_workspace_functions: self.learn_workspace_functions,
+            indent_style: self.indent_style,
+            indent_width: self.indent_width,
+            align_assignments: self.align_assignments,
+            line_ending: self.line_ending,
+            insert_final_newline: self.insert_final_newline,
+            encoding: self.encoding,
+            bom_for_non_ascii: self.bom_for_non_ascii,
+            rules: self.rule_overrides(),
         }
     }
 }
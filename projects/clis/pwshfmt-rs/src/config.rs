@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     cli::Cli,
     error::{AppError, Result},
+    formatter::safety::UnsafeKind,
 };
 
 pub const DEFAULT_CONFIG_FILE: &str = "pwshfmt-rs.toml";
@@ -26,6 +27,212 @@ pub struct Config {
     pub recurse: bool,
     pub strict_fallback: bool,
     pub fallback_script: PathBuf,
+    // 未设置时由旧配置 `strict_fallback` 决定，见 `effective_unsafe_policy`。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsafe_policy: Option<UnsafePolicy>,
+    pub unsafe_allow: Vec<UnsafeAllow>,
+    pub casing_dictionaries: Vec<PathBuf>,
+    pub learn_workspace_functions: bool,
+    pub indent_style: IndentStyle,
+    pub indent_width: usize,
+    pub align_assignments: bool,
+    pub line_ending: LineEnding,
+    pub insert_final_newline: bool,
+    pub encoding: Encoding,
+    pub bom_for_non_ascii: bool,
+    pub rules: Rules,
+}
+
+// 检测到不安全语法时的处理方式；`partial` 只保留不安全语句原样。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnsafePolicy {
+    #[default]
+    Partial,
+    Skip,
+    Fallback,
+    FormatAnyway,
+    Fail,
+}
+
+impl UnsafePolicy {
+    pub fn name(self) -> &'static str {
+        match self {
+            UnsafePolicy::Partial => "partial",
+            UnsafePolicy::Skip => "skip",
+            UnsafePolicy::Fallback => "fallback",
+            UnsafePolicy::FormatAnyway => "format-anyway",
+            UnsafePolicy::Fail => "fail",
+        }
+    }
+}
+
+// 已审查的动态代码：`paths` 为空时作用于所有文件，`kinds` 为空时放行所有种类。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnsafeAllow {
+    pub paths: Vec<String>,
+    pub kinds: Vec<UnsafeKind>,
+}
+
+// `[rules]` 表：每条规则单独开关；别名展开与排版默认关闭，其余默认开启。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub command_case: Rule,
+    pub parameter_case: Rule,
+    pub keyword_case: CaseRule,
+    pub operator_case: Rule,
+    pub type_case: CaseRule,
+    pub variable_case: Rule,
+    pub member_case: Rule,
+    pub alias_expansion: AliasRule,
+    pub layout: Rule,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            command_case: Rule::default(),
+            parameter_case: Rule::default(),
+            keyword_case: CaseRule::default(),
+            operator_case: Rule::default(),
+            type_case: CaseRule::default(),
+            variable_case: Rule::default(),
+            member_case: Rule::default(),
+            alias_expansion: AliasRule::default(),
+            layout: Rule { enabled: false },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rule {
+    pub enabled: bool,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CaseRule {
+    pub enabled: bool,
+    pub style: CaseStyle,
+}
+
+impl Default for CaseRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            style: CaseStyle::Lower,
+        }
+    }
+}
+
+impl CaseRule {
+    // 关闭时等价于 `preserve`。
+    pub fn effective_style(self) -> CaseStyle {
+        if self.enabled {
+            self.style
+        } else {
+            CaseStyle::Preserve
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AliasRule {
+    pub enabled: bool,
+    pub platform: bool,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum RuleName {
+    CommandCase,
+    ParameterCase,
+    KeywordCase,
+    OperatorCase,
+    TypeCase,
+    VariableCase,
+    MemberCase,
+    AliasExpansion,
+    Layout,
+}
+
+impl RuleName {
+    pub fn name(self) -> &'static str {
+        match self {
+            RuleName::CommandCase => "command_case",
+            RuleName::ParameterCase => "parameter_case",
+            RuleName::KeywordCase => "keyword_case",
+            RuleName::OperatorCase => "operator_case",
+            RuleName::TypeCase => "type_case",
+            RuleName::VariableCase => "variable_case",
+            RuleName::MemberCase => "member_case",
+            RuleName::AliasExpansion => "alias_expansion",
+            RuleName::Layout => "layout",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseStyle {
+    #[default]
+    Lower,
+    Upper,
+    Pascal,
+    Preserve,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum IndentStyle {
+    #[default]
+    Space,
+    Tab,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Auto,
+    #[default]
+    Preserve,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Encoding {
+    #[default]
+    Preserve,
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Config {
+    // 旧配置 `strict_fallback = true` 只在未设置 `unsafe_policy` 时等价于 `fallback`。
+    pub fn effective_unsafe_policy(&self) -> UnsafePolicy {
+        match self.unsafe_policy {
+            Some(policy) => policy,
+            None if self.strict_fallback => UnsafePolicy::Fallback,
+            None => UnsafePolicy::Partial,
+        }
+    }
 }
 
 impl Default for Config {
@@ -37,11 +244,30 @@ impl Default for Config {
             recurse: false,
             strict_fallback: false,
             fallback_script: PathBuf::from(DEFAULT_FALLBACK_SCRIPT),
+            unsafe_policy: None,
+            unsafe_allow: Vec::new(),
+            casing_dictionaries: Vec::new(),
+            learn_workspace_functions: false,
+            indent_style: IndentStyle::Space,
+            indent_width: 4,
+            align_assignments: true,
+            line_ending: LineEnding::Preserve,
+            insert_final_newline: false,
+            encoding: Encoding::Preserve,
+            bom_for_non_ascii: false,
+            rules: Rules::default(),
         }
     }
 }
 
 pub fn load(cli: &Cli, cwd: &Path) -> Result<Config> {
+    let config = load_without_validation(cli, cwd)?;
+    validate_config(&config, cwd)?;
+    Ok(config)
+}
+
+// dict 等辅助子命令只读取字典相关配置，不要求目标选择参数。
+pub fn load_without_validation(cli: &Cli, cwd: &Path) -> Result<Config> {
     let config_path = resolve_config_path(cli, cwd);
     let mut figment = Figment::from(Serialized::defaults(Config::default()));
 
@@ -65,7 +291,6 @@ pub fn load(cli: &Cli, cwd: &Path) -> Result<Config> {
 
     normalize_config(&mut config);
     apply_internal_fallback_guard(&mut config);
-    validate_config(&config, cwd)?;
 
     Ok(config)
 }
@@ -101,8 +326,11 @@ fn normalize_config(config: &mut Config) {
 
 fn apply_internal_fallback_guard(config: &mut Config) {
     // 内部回退链路再次启用 strict fallback 会重新调用当前 CLI，必须在配置层断开递归。
-    if config.strict_fallback && env_flag_enabled(FALLBACK_ACTIVE_ENV) {
+    if env_flag_enabled(FALLBACK_ACTIVE_ENV) {
         config.strict_fallback = false;
+        if config.unsafe_policy == Some(UnsafePolicy::Fallback) {
+            config.unsafe_policy = Some(UnsafePolicy::Partial);
+        }
     }
 }
 
@@ -124,7 +352,17 @@ fn validate_config(config: &Config, cwd: &Path) -> Result<()> {
         ));
     }
 
-    if config.strict_fallback {
+    if let Some(policy) = config.unsafe_policy
+        && config.strict_fallback
+        && policy != UnsafePolicy::Fallback
+    {
+        return Err(AppError::invalid_arguments(format!(
+            "strict_fallback = true 与 unsafe_policy = \"{}\" 冲突：请删除旧配置 strict_fallback",
+            policy.name()
+        )));
+    }
+
+    if config.effective_unsafe_policy() == UnsafePolicy::Fallback {
         let script_path = if config.fallback_script.is_absolute() {
             config.fallback_script.clone()
         } else {
@@ -133,11 +371,26 @@ fn validate_config(config: &Config, cwd: &Path) -> Result<()> {
 
         if !script_path.is_file() {
             return Err(AppError::invalid_arguments(format!(
-                "strict_fallback 已启用，但脚本不存在: {}",
+                "unsafe_policy = \"fallback\" 已启用，但严格回退脚本不存在: {}",
                 script_path.display()
             )));
         }
     }
 
+    if config.bom_for_non_ascii && config.encoding == Encoding::Utf8 {
+        return Err(AppError::invalid_arguments(
+            "bom_for_non_ascii 与 encoding = \"utf8\" 冲突：前者要求含非 ASCII 字符的文件带 BOM",
+        ));
+    }
+
+    if config.rules.layout.enabled
+        && config.indent_style == IndentStyle::Space
+        && config.indent_width == 0
+    {
+        return Err(AppError::invalid_arguments(
+            "indent_width 必须大于 0（indent_style = \"space\"）",
+        ));
+    }
+
     Ok(())
 }
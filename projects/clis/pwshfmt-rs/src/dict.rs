@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::cli::{Cli, DictCommand};
+use crate::config;
+use crate::encoding;
+use crate::error::{AppError, Result};
+use crate::formatter::FormatOptions;
+use crate::formatter::dictionary::DictionaryFile;
+
+pub fn run(command: &DictCommand, cli: &Cli, cwd: &Path) -> Result<i32> {
+    match command {
+        DictCommand::Import { dump, output } => {
+            let dump_path = resolve_from_cwd(cwd, dump);
+            let (content, _) = encoding::read_text(&dump_path)
+                .map_err(|source| AppError::io("读取命令转储", &dump_path, source))?;
+            let file =
+                import_command_dump(&content).map_err(|message| AppError::CommandDumpParse {
+                    path: dump_path.clone(),
+                    message,
+                })?;
+
+            emit(
+                &file,
+                output.as_deref().map(|path| resolve_from_cwd(cwd, path)),
+            )?;
+            Ok(0)
+        }
+        DictCommand::Merge { inputs, output } => {
+            let mut file = DictionaryFile::default();
+            for input in inputs {
+                file.merge(&DictionaryFile::load(&resolve_from_cwd(cwd, input))?);
+            }
+
+            emit(
+                &file,
+                output.as_deref().map(|path| resolve_from_cwd(cwd, path)),
+            )?;
+            Ok(0)
+        }
+        DictCommand::Show { name } => {
+            let config = config::load_without_validation(cli, cwd)?;
+            let options = FormatOptions::from_config(&config, cwd)?;
+            Ok(show(&options, name))
+        }
+    }
+}
+
+// 兼容 `Get-Command | Select Name,Parameters | ConvertTo-Json` 与 `Get-Help | ConvertTo-Json` 两种结构。
+pub fn import_command_dump(content: &str) -> std::result::Result<DictionaryFile, String> {
+    let root: Value = serde_json::from_str(content.trim_start_matches('\u{feff}'))
+        .map_err(|error| error.to_string())?;
+
+    let entries = match root {
+        Value::Array(values) => values,
+        value @ Value::Object(_) => vec![value],
+        _ => return Err("转储根节点必须是对象或对象数组".to_string()),
+    };
+
+    let mut file = DictionaryFile::default();
+    for entry in &entries {
+        let Some(name) = field(entry, "Name").and_then(Value::as_str) else {
+            continue;
+        };
+
+        // 原生程序（ApplicationInfo）的 Parameters 为 null，不能当作 PowerShell 命令收录。
+        let parameters = match field(entry, "Parameters") {
+            Some(Value::Null) => continue,
+            Some(value) => parameter_names(value),
+            None => Vec::new(),
+        };
+        if parameters.is_empty() {
+            file.commands.push(name.to_string());
+        } else {
+            file.command_parameters
+                .entry(name.to_string())
+                .or_default()
+                .extend(parameters);
+        }
+    }
+
+    file.normalize();
+    Ok(file)
+}
+
+fn parameter_names(value: &Value) -> Vec<String> {
+    match value {
+        Value::Object(map) => match map
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("parameter"))
+        {
+            Some((_, nested)) => parameter_names(nested),
+            None => map.keys().cloned().collect(),
+        },
+        Value::Array(values) => values
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(name) => Some(name.clone()),
+                Value::Object(_) => field(item, "Name")
+                    .and_then(Value::as_str)
+                    .map(ToOwned::to_owned),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, field_value)| field_value)
+}
+
+fn emit(file: &DictionaryFile, output: Option<PathBuf>) -> Result<()> {
+    match output {
+        Some(path) => {
+            file.save(&path)?;
+            println!(
+                "INFO 已写入字典 {} (commands={})",
+                path.display(),
+                file.commands.len()
+            );
+        }
+        None => print!("{}", file.render(false)),
+    }
+    Ok(())
+}
+
+fn show(options: &FormatOptions, name: &str) -> i32 {
+    let dictionary = &options.dictionary;
+    let mut found = false;
+
+    if let Some(command) = dictionary.command(name) {
+        found = true;
+        let parameters = dictionary.parameters_of(command);
+        if parameters.is_empty() {
+            println!("COMMAND {command}");
+        } else {
+            println!("COMMAND {command} (parameters={})", parameters.join(", "));
+        }
+    }
+
+    let parameter_name = name.trim_start_matches('-');
+    if let Some(parameter) = dictionary.parameter(parameter_name) {
+        found = true;
+        println!("PARAMETER -{parameter}");
+    }
+
+    if let Some(member) = dictionary.member(name.trim_start_matches(['.', ':'])) {
+        found = true;
+        println!("MEMBER {member}");
+    }
+
+    let owners = dictionary.commands_with_parameter(parameter_name);
+    if !owners.is_empty() {
+        found = true;
+        for (command, parameter) in owners {
+            println!("PARAMETER -{parameter} (command={command})");
+        }
+    }
+
+    if found {
+        0
+    } else {
+        eprintln!("NOT_FOUND {name} (当前生效的大小写字典中无此条目)");
+        1
+    }
+}
+
+fn resolve_from_cwd(cwd: &Path, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        cwd.join(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::import_command_dump;
+
+    #[test]
+    fn imports_get_command_dump_with_parameter_dictionaries() {
+        let dump = r#"[
+            { "Name": "Invoke-Pester", "Parameters": { "Path": "System.Management.Automation.ParameterMetadata", "CI": {} } },
+            { "Name": "git.exe", "Parameters": null }
+        ]"#;
+
+        let file = import_command_dump(dump).expect("import dump");
+
+        assert_eq!(file.commands, vec!["Invoke-Pester"]);
+        assert_eq!(
+            file.command_parameters,
+            BTreeMap::from([(
+                "Invoke-Pester".to_string(),
+                vec!["CI".to_string(), "Path".to_string()]
+            )])
+        );
+    }
+
+    #[test]
+    fn imports_single_get_help_object() {
+        let dump = "\u{feff}{ \"Name\": \"Get-Foo\", \"parameters\": { \"parameter\": [ { \"name\": \"LiteralPath\" }, { \"name\": \"Force\" } ] } }";
+
+        let file = import_command_dump(dump).expect("import help dump");
+
+        assert_eq!(
+            file.command_parameters.get("Get-Foo"),
+            Some(&vec!["Force".to_string(), "LiteralPath".to_string()])
+        );
+    }
+
+    #[test]
+    fn rejects_non_object_dump() {
+        assert!(import_command_dump("42").is_err());
+    }
+}
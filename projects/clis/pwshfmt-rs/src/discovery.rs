@@ -2,7 +2,7 @@ use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use globset::GlobBuilder;
+use globset::{GlobBuilder, GlobMatcher};
 use walkdir::WalkDir;
 
 use crate::{
@@ -194,15 +194,7 @@ fn collect_files_from_pattern(
     excluded_roots: &[PathBuf],
     deduped: &mut BTreeSet<PathBuf>,
 ) -> Result<()> {
-    let normalized_pattern = pattern.replace('\\', "/");
-    let glob = GlobBuilder::new(&normalized_pattern)
-        .literal_separator(false)
-        .build()
-        .map_err(|source| AppError::InvalidGlob {
-            pattern: pattern.to_string(),
-            source,
-        })?;
-    let matcher = glob.compile_matcher();
+    let matcher = compile_glob(pattern)?;
 
     let base_dir = pattern_base_dir(pattern);
     let base_abs = resolve_from_cwd(cwd, &base_dir);
@@ -256,6 +248,26 @@ fn collect_files_from_pattern(
     Ok(())
 }
 
+pub fn compile_glob(pattern: &str) -> Result<GlobMatcher> {
+    let normalized_pattern = pattern.replace('\\', "/");
+    let glob = GlobBuilder::new(&normalized_pattern)
+        .literal_separator(false)
+        .build()
+        .map_err(|source| AppError::InvalidGlob {
+            pattern: pattern.to_string(),
+            source,
+        })?;
+    Ok(glob.compile_matcher())
+}
+
+// 与发现阶段一致：绝对路径或相对 cwd 的路径任一匹配即可。
+pub fn glob_matches(matcher: &GlobMatcher, path: &Path, cwd: &Path) -> bool {
+    let cwd = normalize_existing_path(cwd);
+    let relative = path.strip_prefix(&cwd).unwrap_or(path);
+    matcher.is_match(Path::new(&normalize_for_glob(path)))
+        || matcher.is_match(Path::new(&normalize_for_glob(relative)))
+}
+
 fn pattern_base_dir(pattern: &str) -> PathBuf {
     let wildcard_index = pattern
         .char_indices()
@@ -0,0 +1,165 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config::Encoding;
+
+const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: [u8; 2] = [0xFF, 0xFE];
+const UTF16_BE_BOM: [u8; 2] = [0xFE, 0xFF];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl TextEncoding {
+    // `encoding = "preserve"` 沿用读取时识别出的编码。
+    pub fn resolve(setting: Encoding, detected: TextEncoding) -> TextEncoding {
+        match setting {
+            Encoding::Preserve => detected,
+            Encoding::Utf8 => TextEncoding::Utf8,
+            Encoding::Utf8Bom => TextEncoding::Utf8Bom,
+            Encoding::Utf16Le => TextEncoding::Utf16Le,
+            Encoding::Utf16Be => TextEncoding::Utf16Be,
+        }
+    }
+}
+
+// 按 BOM 识别编码；Windows PowerShell 5.1 的 `Out-File` 默认写出带 BOM 的 UTF-16 LE。
+pub fn decode(bytes: &[u8]) -> Result<(String, TextEncoding), String> {
+    if let Some(rest) = bytes.strip_prefix(&UTF8_BOM) {
+        return decode_utf8(rest).map(|text| (text, TextEncoding::Utf8Bom));
+    }
+    if let Some(rest) = bytes.strip_prefix(&UTF16_LE_BOM) {
+        return decode_utf16(rest, u16::from_le_bytes).map(|text| (text, TextEncoding::Utf16Le));
+    }
+    if let Some(rest) = bytes.strip_prefix(&UTF16_BE_BOM) {
+        return decode_utf16(rest, u16::from_be_bytes).map(|text| (text, TextEncoding::Utf16Be));
+    }
+    decode_utf8(bytes).map(|text| (text, TextEncoding::Utf8))
+}
+
+pub fn read_text(path: &Path) -> std::io::Result<(String, TextEncoding)> {
+    let bytes = fs::read(path)?;
+    decode(&bytes).map_err(|message| std::io::Error::new(std::io::ErrorKind::InvalidData, message))
+}
+
+pub fn encode(text: &str, encoding: TextEncoding) -> Vec<u8> {
+    match encoding {
+        TextEncoding::Utf8 => text.as_bytes().to_vec(),
+        TextEncoding::Utf8Bom => [&UTF8_BOM[..], text.as_bytes()].concat(),
+        TextEncoding::Utf16Le => UTF16_LE_BOM
+            .into_iter()
+            .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect(),
+        TextEncoding::Utf16Be => UTF16_BE_BOM
+            .into_iter()
+            .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+            .collect(),
+    }
+}
+
+// Windows PowerShell 5.1 把无 BOM 的 UTF-8 脚本按系统 ANSI 代码页读取，只对其会加载的脚本类型生效。
+pub fn requires_bom(path: &Path) -> bool {
+    path.extension()
+        .and_then(|value| value.to_str())
+        .is_some_and(|extension| {
+            ["ps1", "psm1", "psd1"]
+                .iter()
+                .any(|candidate| candidate.eq_ignore_ascii_case(extension))
+        })
+}
+
+// 返回首个含非 ASCII 字符的行号（从 1 开始）。
+pub fn first_non_ascii_line(text: &str) -> Option<usize> {
+    text.lines()
+        .position(|line| !line.is_ascii())
+        .map(|index| index + 1)
+}
+
+fn decode_utf8(bytes: &[u8]) -> Result<String, String> {
+    String::from_utf8(bytes.to_vec()).map_err(|error| {
+        format!(
+            "不是有效的 UTF-8 文本（字节偏移 {}），如为 ANSI/GBK 编码请先转换",
+            error.utf8_error().valid_up_to()
+        )
+    })
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Result<String, String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err("UTF-16 内容字节数为奇数".to_string());
+    }
+
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|error| format!("不是有效的 UTF-16 文本: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{TextEncoding, decode, encode, first_non_ascii_line, requires_bom};
+    use crate::config::Encoding;
+
+    #[test]
+    fn round_trips_every_supported_encoding() {
+        let text = "Write-Host '中文'\r\n";
+        for encoding in [
+            TextEncoding::Utf8,
+            TextEncoding::Utf8Bom,
+            TextEncoding::Utf16Le,
+            TextEncoding::Utf16Be,
+        ] {
+            let bytes = encode(text, encoding);
+            assert_eq!(decode(&bytes), Ok((text.to_string(), encoding)));
+        }
+    }
+
+    #[test]
+    fn detects_byte_order_marks() {
+        assert_eq!(
+            encode("a", TextEncoding::Utf16Le),
+            vec![0xFF, 0xFE, b'a', 0]
+        );
+        assert_eq!(
+            encode("a", TextEncoding::Utf16Be),
+            vec![0xFE, 0xFF, 0, b'a']
+        );
+        assert_eq!(
+            decode(&[0xEF, 0xBB, 0xBF, b'a']),
+            Ok(("a".to_string(), TextEncoding::Utf8Bom))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_content() {
+        assert!(decode(&[0xC4, 0xE3]).is_err());
+        assert!(decode(&[0xFF, 0xFE, b'a']).is_err());
+    }
+
+    #[test]
+    fn resolves_forced_target_encoding() {
+        assert_eq!(
+            TextEncoding::resolve(Encoding::Preserve, TextEncoding::Utf16Le),
+            TextEncoding::Utf16Le
+        );
+        assert_eq!(
+            TextEncoding::resolve(Encoding::Utf8, TextEncoding::Utf16Le),
+            TextEncoding::Utf8
+        );
+    }
+
+    #[test]
+    fn finds_first_non_ascii_line_in_powershell_files() {
+        assert_eq!(first_non_ascii_line("a\r\nb\n# 中文\n"), Some(3));
+        assert_eq!(first_non_ascii_line("a\nb\n"), None);
+        assert!(requires_bom(Path::new("scripts/Profile.PSM1")));
+        assert!(!requires_bom(Path::new("scripts/readme.md")));
+    }
+}
@@ -44,6 +44,14 @@ pub enum AppError {
     #[diagnostic(code(pwshfmt::discovery::git_failed))]
     GitCommandFailed { message: String },
 
+    #[error("大小写字典解析失败: {path} ({message})")]
+    #[diagnostic(code(pwshfmt::dictionary::parse))]
+    DictionaryParse { path: PathBuf, message: String },
+
+    #[error("命令转储解析失败: {path} ({message})")]
+    #[diagnostic(code(pwshfmt::dictionary::dump_parse))]
+    CommandDumpParse { path: PathBuf, message: String },
+
     #[error("严格回退失败: {path} ({message})")]
     #[diagnostic(code(pwshfmt::fallback::failed))]
     FallbackFailed { path: PathBuf, message: String },
@@ -0,0 +1,117 @@
+// 各平台都存在的内置别名。
+const ALIASES: [(&str, &str); 62] = [
+    ("%", "ForEach-Object"),
+    ("?", "Where-Object"),
+    ("cd", "Set-Location"),
+    ("chdir", "Set-Location"),
+    ("clc", "Clear-Content"),
+    ("cli", "Clear-Item"),
+    ("clp", "Clear-ItemProperty"),
+    ("cls", "Clear-Host"),
+    ("clv", "Clear-Variable"),
+    ("copy", "Copy-Item"),
+    ("cpi", "Copy-Item"),
+    ("cvpa", "Convert-Path"),
+    ("del", "Remove-Item"),
+    ("dir", "Get-ChildItem"),
+    ("echo", "Write-Output"),
+    ("epcsv", "Export-Csv"),
+    ("erase", "Remove-Item"),
+    ("fl", "Format-List"),
+    ("foreach", "ForEach-Object"),
+    ("ft", "Format-Table"),
+    ("fw", "Format-Wide"),
+    ("gal", "Get-Alias"),
+    ("gc", "Get-Content"),
+    ("gci", "Get-ChildItem"),
+    ("gcm", "Get-Command"),
+    ("gdr", "Get-PSDrive"),
+    ("gi", "Get-Item"),
+    ("gl", "Get-Location"),
+    ("gm", "Get-Member"),
+    ("gmo", "Get-Module"),
+    ("gp", "Get-ItemProperty"),
+    ("gps", "Get-Process"),
+    ("group", "Group-Object"),
+    ("gv", "Get-Variable"),
+    ("icm", "Invoke-Command"),
+    ("iex", "Invoke-Expression"),
+    ("ii", "Invoke-Item"),
+    ("ipcsv", "Import-Csv"),
+    ("ipmo", "Import-Module"),
+    ("irm", "Invoke-RestMethod"),
+    ("iwr", "Invoke-WebRequest"),
+    ("measure", "Measure-Object"),
+    ("mi", "Move-Item"),
+    ("move", "Move-Item"),
+    ("ni", "New-Item"),
+    ("nv", "New-Variable"),
+    ("oh", "Out-Host"),
+    ("popd", "Pop-Location"),
+    ("pushd", "Push-Location"),
+    ("pwd", "Get-Location"),
+    ("rd", "Remove-Item"),
+    ("ren", "Rename-Item"),
+    ("ri", "Remove-Item"),
+    ("rni", "Rename-Item"),
+    ("rnp", "Rename-ItemProperty"),
+    ("rv", "Remove-Variable"),
+    ("select", "Select-Object"),
+    ("sl", "Set-Location"),
+    ("sls", "Select-String"),
+    ("sp", "Set-ItemProperty"),
+    ("sv", "Set-Variable"),
+    ("where", "Where-Object"),
+];
+
+// 仅 Windows 上存在的别名；在 Linux/macOS 上同名的是原生程序（`ls`、`cat`），需单独开启。
+const PLATFORM_ALIASES: [(&str, &str); 20] = [
+    ("ac", "Add-Content"),
+    ("cat", "Get-Content"),
+    ("clear", "Clear-Host"),
+    ("compare", "Compare-Object"),
+    ("cp", "Copy-Item"),
+    ("cpp", "Copy-ItemProperty"),
+    ("diff", "Compare-Object"),
+    ("kill", "Stop-Process"),
+    ("ls", "Get-ChildItem"),
+    ("man", "help"),
+    ("mount", "New-PSDrive"),
+    ("mv", "Move-Item"),
+    ("ps", "Get-Process"),
+    ("rm", "Remove-Item"),
+    ("rmdir", "Remove-Item"),
+    ("sleep", "Start-Sleep"),
+    ("sort", "Sort-Object"),
+    ("start", "Start-Process"),
+    ("tee", "Tee-Object"),
+    ("write", "Write-Output"),
+];
+
+pub fn resolve_alias(name: &str, include_platform: bool) -> Option<&'static str> {
+    let platform: &[(&str, &str)] = if include_platform {
+        &PLATFORM_ALIASES
+    } else {
+        &[]
+    };
+
+    ALIASES
+        .iter()
+        .chain(platform)
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map(|(_, command)| *command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_alias;
+
+    #[test]
+    fn resolves_platform_aliases_only_when_enabled() {
+        assert_eq!(resolve_alias("GCI", false), Some("Get-ChildItem"));
+        assert_eq!(resolve_alias("%", false), Some("ForEach-Object"));
+        assert_eq!(resolve_alias("ls", false), None);
+        assert_eq!(resolve_alias("ls", true), Some("Get-ChildItem"));
+        assert_eq!(resolve_alias("git", true), None);
+    }
+}
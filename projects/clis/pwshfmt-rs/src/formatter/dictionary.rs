@@ -0,0 +1,298 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::encoding;
+use crate::error::{AppError, Result};
+
+const BUILTIN_DICTIONARY: &str = include_str!("data/builtin.toml");
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DictionaryFile {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub command_parameters: BTreeMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+}
+
+impl DictionaryFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let (content, _) = encoding::read_text(path)
+            .map_err(|source| AppError::io("读取大小写字典", path, source))?;
+        let content = content.as_str();
+
+        let parsed = if is_json_path(path) {
+            serde_json::from_str(content).map_err(|error| error.to_string())
+        } else {
+            toml::from_str(content).map_err(|error| error.to_string())
+        };
+
+        parsed.map_err(|message| AppError::DictionaryParse {
+            path: path.to_path_buf(),
+            message,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = self.render(is_json_path(path));
+        fs::write(path, content).map_err(|source| AppError::io("写入大小写字典", path, source))
+    }
+
+    pub fn render(&self, json: bool) -> String {
+        if json {
+            let mut content =
+                serde_json::to_string_pretty(self).expect("字典结构必须可序列化为 JSON");
+            content.push('\n');
+            content
+        } else {
+            toml::to_string_pretty(self).expect("字典结构必须可序列化为 TOML")
+        }
+    }
+
+    // 合并时后出现的写法覆盖先前同名（大小写不敏感）条目。
+    pub fn merge(&mut self, other: &DictionaryFile) {
+        self.commands.extend(other.commands.iter().cloned());
+        self.parameters.extend(other.parameters.iter().cloned());
+        self.members.extend(other.members.iter().cloned());
+
+        for (command, parameters) in &other.command_parameters {
+            let existing_key = self
+                .command_parameters
+                .keys()
+                .find(|key| key.eq_ignore_ascii_case(command))
+                .cloned();
+            let mut merged = existing_key
+                .and_then(|key| self.command_parameters.remove(&key))
+                .unwrap_or_default();
+            merged.extend(parameters.iter().cloned());
+            self.command_parameters.insert(command.clone(), merged);
+        }
+
+        self.normalize();
+    }
+
+    pub fn normalize(&mut self) {
+        let mut commands: Vec<String> = self.command_parameters.keys().cloned().collect();
+        commands.append(&mut self.commands);
+        self.commands = dedupe_names(commands);
+        self.parameters = dedupe_names(std::mem::take(&mut self.parameters));
+        self.members = dedupe_names(std::mem::take(&mut self.members));
+
+        let command_parameters = std::mem::take(&mut self.command_parameters);
+        for (command, parameters) in command_parameters {
+            self.command_parameters
+                .insert(command.trim().to_string(), dedupe_names(parameters));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CasingDictionary {
+    commands: HashMap<String, String>,
+    parameters: HashMap<String, String>,
+    command_parameters: HashMap<String, HashMap<String, String>>,
+    members: HashMap<String, String>,
+}
+
+impl CasingDictionary {
+    pub fn builtin() -> &'static Self {
+        static BUILTIN: OnceLock<CasingDictionary> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let file: DictionaryFile =
+                toml::from_str(BUILTIN_DICTIONARY).expect("内置大小写字典必须是合法 TOML");
+            let mut dictionary = CasingDictionary::default();
+            dictionary.merge(&file);
+            dictionary
+        })
+    }
+
+    pub fn load(paths: &[impl AsRef<Path>]) -> Result<Self> {
+        let mut dictionary = Self::builtin().clone();
+        for path in paths {
+            dictionary.merge(&DictionaryFile::load(path.as_ref())?);
+        }
+        Ok(dictionary)
+    }
+
+    // 后合并的条目覆盖先前同名（大小写不敏感）条目，保证用户字典可修正内置写法。
+    pub fn merge(&mut self, file: &DictionaryFile) {
+        insert_all(&mut self.commands, &file.commands);
+        insert_all(&mut self.parameters, &file.parameters);
+        insert_all(&mut self.members, &file.members);
+
+        for (command, parameters) in &file.command_parameters {
+            insert_all(&mut self.commands, std::slice::from_ref(command));
+            insert_all(
+                self.command_parameters
+                    .entry(command.trim().to_ascii_lowercase())
+                    .or_default(),
+                parameters,
+            );
+        }
+    }
+
+    pub fn command(&self, name: &str) -> Option<&str> {
+        self.commands
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    pub fn member(&self, name: &str) -> Option<&str> {
+        self.members
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    pub fn command_parameter(&self, command: &str, name: &str) -> Option<&str> {
+        self.command_parameters
+            .get(&command.to_ascii_lowercase())?
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    pub fn commands_with_parameter(&self, name: &str) -> Vec<(&str, &str)> {
+        let name_lower = name.to_ascii_lowercase();
+        let mut owners: Vec<(&str, &str)> = self
+            .command_parameters
+            .iter()
+            .filter_map(|(command, parameters)| {
+                let parameter = parameters.get(&name_lower)?;
+                let command = self.commands.get(command)?;
+                Some((command.as_str(), parameter.as_str()))
+            })
+            .collect();
+        owners.sort_by_key(|(command, _)| command.to_ascii_lowercase());
+        owners
+    }
+
+    pub fn command_count(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn parameters_of(&self, command: &str) -> Vec<&str> {
+        let mut parameters: Vec<&str> = self
+            .command_parameters
+            .get(&command.to_ascii_lowercase())
+            .map(|values| values.values().map(String::as_str).collect())
+            .unwrap_or_default();
+        parameters.sort_by_key(|value| value.to_ascii_lowercase());
+        parameters
+    }
+}
+
+fn insert_all(target: &mut HashMap<String, String>, names: &[String]) {
+    for name in names.iter().map(|value| value.trim()) {
+        if !name.is_empty() {
+            target.insert(name.to_ascii_lowercase(), name.to_string());
+        }
+    }
+}
+
+fn dedupe_names(names: Vec<String>) -> Vec<String> {
+    let mut unique = BTreeMap::new();
+    for name in names {
+        let name = name.trim();
+        if !name.is_empty() {
+            unique.insert(name.to_ascii_lowercase(), name.to_string());
+        }
+    }
+    unique.into_values().collect()
+}
+
+fn is_json_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|value| value.to_str())
+        .is_some_and(|value| value.eq_ignore_ascii_case("json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{CasingDictionary, DictionaryFile};
+
+    #[test]
+    fn builtin_dictionary_resolves_case_insensitively() {
+        let dictionary = CasingDictionary::builtin();
+
+        assert_eq!(dictionary.command("GET-CHILDITEM"), Some("Get-ChildItem"));
+        assert_eq!(dictionary.parameter("literalpath"), Some("LiteralPath"));
+        assert_eq!(dictionary.command("Invoke-Pester"), None);
+    }
+
+    #[test]
+    fn merged_entries_extend_and_override_builtin_names() {
+        let mut dictionary = CasingDictionary::builtin().clone();
+        dictionary.merge(&DictionaryFile {
+            commands: vec!["Invoke-Pester".to_string(), "Get-Childitem".to_string()],
+            parameters: vec!["CI".to_string()],
+            command_parameters: BTreeMap::from([(
+                "New-PesterConfiguration".to_string(),
+                vec!["Hashtable".to_string()],
+            )]),
+            members: vec!["ToJson".to_string()],
+        });
+
+        assert_eq!(dictionary.command("invoke-pester"), Some("Invoke-Pester"));
+        assert_eq!(dictionary.member("TOJSON"), Some("ToJson"));
+        assert_eq!(dictionary.member("isnullorempty"), Some("IsNullOrEmpty"));
+        assert_eq!(dictionary.command("get-childitem"), Some("Get-Childitem"));
+        assert_eq!(dictionary.parameter("ci"), Some("CI"));
+        assert_eq!(dictionary.parameter("hashtable"), None);
+        assert_eq!(
+            dictionary.command_parameter("new-pesterconfiguration", "HASHTABLE"),
+            Some("Hashtable")
+        );
+        assert_eq!(
+            dictionary.command("new-pesterconfiguration"),
+            Some("New-PesterConfiguration")
+        );
+        assert_eq!(
+            dictionary.parameters_of("NEW-PESTERCONFIGURATION"),
+            vec!["Hashtable"]
+        );
+    }
+
+    #[test]
+    fn file_merge_dedupes_case_insensitively_with_later_entries_winning() {
+        let mut file = DictionaryFile {
+            commands: vec!["get-foo".to_string()],
+            parameters: Vec::new(),
+            command_parameters: BTreeMap::from([("get-foo".to_string(), vec!["path".to_string()])]),
+            members: Vec::new(),
+        };
+        file.merge(&DictionaryFile {
+            commands: vec!["Get-Foo".to_string(), "Add-Bar".to_string()],
+            parameters: vec!["Force".to_string()],
+            command_parameters: BTreeMap::from([(
+                "Get-Foo".to_string(),
+                vec!["Path".to_string(), "Name".to_string()],
+            )]),
+            members: Vec::new(),
+        });
+
+        assert_eq!(file.commands, vec!["Add-Bar", "Get-Foo"]);
+        assert_eq!(file.parameters, vec!["Force"]);
+        assert_eq!(
+            file.command_parameters,
+            BTreeMap::from([(
+                "Get-Foo".to_string(),
+                vec!["Name".to_string(), "Path".to_string()]
+            )])
+        );
+    }
+}
@@ -0,0 +1,250 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSource {
+    Dictionary,
+    Workspace,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveCommand {
+    pub name: String,
+    pub source: CommandSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Invocation {
+    Idle,
+    CallTarget,
+    Declaration,
+    Expression,
+    Attribute,
+    Command(ActiveCommand),
+    Native,
+}
+
+const APPROVED_VERBS: [&str; 100] = [
+    "add",
+    "approve",
+    "assert",
+    "backup",
+    "block",
+    "build",
+    "checkpoint",
+    "clear",
+    "close",
+    "compare",
+    "complete",
+    "compress",
+    "confirm",
+    "connect",
+    "convert",
+    "convertfrom",
+    "convertto",
+    "copy",
+    "debug",
+    "deny",
+    "deploy",
+    "disable",
+    "disconnect",
+    "dismount",
+    "edit",
+    "enable",
+    "enter",
+    "exit",
+    "expand",
+    "export",
+    "find",
+    "format",
+    "get",
+    "grant",
+    "group",
+    "hide",
+    "import",
+    "initialize",
+    "install",
+    "invoke",
+    "join",
+    "limit",
+    "lock",
+    "measure",
+    "merge",
+    "mount",
+    "move",
+    "new",
+    "open",
+    "optimize",
+    "out",
+    "ping",
+    "pop",
+    "protect",
+    "publish",
+    "push",
+    "read",
+    "receive",
+    "redo",
+    "register",
+    "remove",
+    "rename",
+    "repair",
+    "request",
+    "reset",
+    "resize",
+    "resolve",
+    "restart",
+    "restore",
+    "resume",
+    "revoke",
+    "save",
+    "search",
+    "select",
+    "send",
+    "set",
+    "show",
+    "skip",
+    "split",
+    "start",
+    "step",
+    "stop",
+    "submit",
+    "suspend",
+    "switch",
+    "sync",
+    "test",
+    "trace",
+    "unblock",
+    "undo",
+    "uninstall",
+    "unlock",
+    "unprotect",
+    "unpublish",
+    "unregister",
+    "update",
+    "use",
+    "wait",
+    "watch",
+    "write",
+];
+
+// 未收录的 `批准动词-名词` 视为 PowerShell 命令（回退全局参数表）；
+// 裸名、路径与 `start-stop-daemon` 这类多段名称按原生程序处理。
+pub fn looks_like_powershell_command(name: &str) -> bool {
+    let Some((verb, noun)) = name.split_once('-') else {
+        return false;
+    };
+
+    !noun.is_empty()
+        && noun
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric())
+        && APPROVED_VERBS
+            .iter()
+            .any(|candidate| candidate.eq_ignore_ascii_case(verb))
+}
+
+// 每层括号/脚本块一个帧：嵌套调用结束后回到外层命令继续解析其余参数。
+#[derive(Debug, Clone)]
+pub struct InvocationTracker {
+    frames: Vec<Invocation>,
+}
+
+impl Default for InvocationTracker {
+    fn default() -> Self {
+        Self {
+            frames: vec![Invocation::Idle],
+        }
+    }
+}
+
+impl InvocationTracker {
+    pub fn enter_group(&mut self) {
+        self.frames.push(Invocation::Idle);
+    }
+
+    pub fn leave_group(&mut self) {
+        if self.frames.len() > 1 {
+            self.frames.pop();
+        }
+    }
+
+    // 特性参数列表里的换行与 `Position = 0` 不是语句边界。
+    pub fn end_statement(&mut self) {
+        if self.current() != &Invocation::Attribute {
+            self.set(Invocation::Idle);
+        }
+    }
+
+    pub fn set(&mut self, invocation: Invocation) {
+        if let Some(frame) = self.frames.last_mut() {
+            *frame = invocation;
+        }
+    }
+
+    pub fn begin_command(&mut self, name: &str, source: CommandSource) {
+        self.set(Invocation::Command(ActiveCommand {
+            name: name.to_string(),
+            source,
+        }));
+    }
+
+    pub fn current(&self) -> &Invocation {
+        self.frames.last().unwrap_or(&Invocation::Idle)
+    }
+
+    pub fn current_command(&self) -> Option<&ActiveCommand> {
+        match self.current() {
+            Invocation::Command(command) => Some(command),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ActiveCommand, CommandSource, Invocation, InvocationTracker, looks_like_powershell_command,
+    };
+
+    fn command(name: &str) -> ActiveCommand {
+        ActiveCommand {
+            name: name.to_string(),
+            source: CommandSource::Dictionary,
+        }
+    }
+
+    #[test]
+    fn restores_outer_command_after_nested_group() {
+        let mut tracker = InvocationTracker::default();
+        tracker.begin_command("Get-ChildItem", CommandSource::Dictionary);
+        tracker.enter_group();
+        tracker.begin_command("Join-Path", CommandSource::Dictionary);
+        assert_eq!(tracker.current_command(), Some(&command("Join-Path")));
+
+        tracker.leave_group();
+        assert_eq!(tracker.current_command(), Some(&command("Get-ChildItem")));
+
+        tracker.end_statement();
+        assert_eq!(tracker.current(), &Invocation::Idle);
+    }
+
+    #[test]
+    fn unbalanced_close_keeps_root_frame() {
+        let mut tracker = InvocationTracker::default();
+        tracker.leave_group();
+        tracker.set(Invocation::Native);
+
+        assert_eq!(tracker.current(), &Invocation::Native);
+        assert_eq!(tracker.current_command(), None);
+    }
+
+    #[test]
+    fn recognizes_approved_verb_noun_names() {
+        assert!(looks_like_powershell_command("Invoke-Build"));
+        assert!(looks_like_powershell_command("copy-item"));
+        assert!(!looks_like_powershell_command("start-stop-daemon"));
+        assert!(!looks_like_powershell_command("ssh-keygen"));
+        assert!(!looks_like_powershell_command("docker-compose"));
+        assert!(!looks_like_powershell_command("./get-x"));
+        assert!(!looks_like_powershell_command("get-x.exe"));
+        assert!(!looks_like_powershell_command("git"));
+    }
+}
@@ -0,0 +1,236 @@
+use super::lexer::{Token, TokenKind};
+
+// 以 PascalCase 记录规范写法，供 `pascal` 风格直接使用。
+const KEYWORDS: [&str; 38] = [
+    "Begin",
+    "Break",
+    "Catch",
+    "Class",
+    "Clean",
+    "Configuration",
+    "Continue",
+    "Data",
+    "Define",
+    "Do",
+    "DynamicParam",
+    "Else",
+    "ElseIf",
+    "End",
+    "Enum",
+    "Exit",
+    "Filter",
+    "Finally",
+    "For",
+    "ForEach",
+    "From",
+    "Function",
+    "Hidden",
+    "If",
+    "In",
+    "Param",
+    "Process",
+    "Return",
+    "Static",
+    "Switch",
+    "Throw",
+    "Trap",
+    "Try",
+    "Until",
+    "Using",
+    "Var",
+    "While",
+    "Workflow",
+];
+
+// 只在 workflow 体内是关键字；其他位置的 `parallel` 等通常是区分大小写的原生程序。
+const WORKFLOW_KEYWORDS: [&str; 3] = ["InlineScript", "Parallel", "Sequence"];
+
+pub fn keyword(text: &str) -> Option<&'static str> {
+    KEYWORDS
+        .iter()
+        .find(|keyword| keyword.eq_ignore_ascii_case(text))
+        .copied()
+}
+
+pub fn workflow_keyword(text: &str) -> Option<&'static str> {
+    WORKFLOW_KEYWORDS
+        .iter()
+        .find(|keyword| keyword.eq_ignore_ascii_case(text))
+        .copied()
+}
+
+// `workflow Name { ... }` 的左右大括号下标；缺少右括号时延伸到文件末尾。
+pub fn workflow_bodies(tokens: &[Token], input: &str) -> Vec<(usize, usize)> {
+    let mut bodies = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Word || !token.text(input).eq_ignore_ascii_case("workflow") {
+            continue;
+        }
+        let Some(open) = tokens[index + 1..]
+            .iter()
+            .position(|token| {
+                !matches!(
+                    token.kind,
+                    TokenKind::Whitespace | TokenKind::NewLine | TokenKind::Word
+                )
+            })
+            .map(|offset| index + 1 + offset)
+            .filter(|open| tokens[*open].kind == TokenKind::LBrace)
+        else {
+            continue;
+        };
+
+        let mut depth = 0usize;
+        let mut close = tokens.len();
+        for (candidate, token) in tokens.iter().enumerate().skip(open + 1) {
+            match token.kind {
+                TokenKind::LBrace | TokenKind::HashtableStart => depth += 1,
+                TokenKind::RBrace if depth == 0 => {
+                    close = candidate;
+                    break;
+                }
+                TokenKind::RBrace => depth -= 1,
+                _ => {}
+            }
+        }
+        bodies.push((open, close));
+    }
+    bodies
+}
+
+// `class`/`enum` 体内直接声明的成员名：方法与构造函数名（后跟 `(`）以及枚举值。
+pub fn member_declarations(tokens: &[Token], input: &str) -> Vec<usize> {
+    let mut members = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Word {
+            continue;
+        }
+        let text = token.text(input);
+        let is_enum = text.eq_ignore_ascii_case("enum");
+        if !is_enum && !text.eq_ignore_ascii_case("class") {
+            continue;
+        }
+        let Some(open) = declaration_body(tokens, index) else {
+            continue;
+        };
+
+        let mut depth = 0usize;
+        for (candidate, token) in tokens.iter().enumerate().skip(open + 1) {
+            match token.kind {
+                TokenKind::LParen
+                | TokenKind::SubExpressionStart
+                | TokenKind::ArrayExpressionStart
+                | TokenKind::LBrace
+                | TokenKind::HashtableStart
+                | TokenKind::LBracket => depth += 1,
+                TokenKind::RBrace if depth == 0 => break,
+                TokenKind::RParen | TokenKind::RBrace | TokenKind::RBracket => {
+                    depth = depth.saturating_sub(1);
+                }
+                TokenKind::Word if depth == 0 => {
+                    let next = tokens[candidate + 1..]
+                        .iter()
+                        .find(|token| token.kind != TokenKind::Whitespace)
+                        .map(|token| token.kind);
+                    if is_enum || next == Some(TokenKind::LParen) {
+                        members.push(candidate);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    members.sort_unstable();
+    members
+}
+
+// `class Name : Base, IFace {` 中声明体的左大括号；关键字后不是名称时不是声明。
+fn declaration_body(tokens: &[Token], keyword: usize) -> Option<usize> {
+    let mut rest = tokens[keyword + 1..]
+        .iter()
+        .enumerate()
+        .map(|(offset, token)| (keyword + 1 + offset, token.kind))
+        .filter(|(_, kind)| !kind.is_trivia());
+    if rest.next()?.1 != TokenKind::Word {
+        return None;
+    }
+    rest.find(|(_, kind)| {
+        !matches!(
+            kind,
+            TokenKind::Word
+                | TokenKind::Comma
+                | TokenKind::Dot
+                | TokenKind::NewLine
+                | TokenKind::Operator
+        )
+    })
+    .filter(|(_, kind)| *kind == TokenKind::LBrace)
+    .map(|(open, _)| open)
+}
+
+// 声明关键字后紧跟的是被声明的名称，而不是要调用的命令。
+pub fn is_declaration_keyword(text: &str) -> bool {
+    [
+        "function",
+        "filter",
+        "workflow",
+        "configuration",
+        "class",
+        "enum",
+    ]
+    .iter()
+    .any(|keyword| keyword.eq_ignore_ascii_case(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_declaration_keyword, keyword, member_declarations, workflow_bodies, workflow_keyword,
+    };
+    use crate::formatter::lexer::tokenize;
+
+    fn member_names(input: &str) -> Vec<&str> {
+        let tokens = tokenize(input);
+        member_declarations(&tokens, input)
+            .into_iter()
+            .map(|index| tokens[index].text(input))
+            .collect()
+    }
+
+    #[test]
+    fn resolves_keywords_case_insensitively() {
+        assert_eq!(keyword("ELSEIF"), Some("ElseIf"));
+        assert_eq!(keyword("dynamicparam"), Some("DynamicParam"));
+        assert_eq!(keyword("parallel"), None);
+        assert_eq!(workflow_keyword("INLINESCRIPT"), Some("InlineScript"));
+        assert_eq!(workflow_keyword("if"), None);
+        assert!(is_declaration_keyword("Enum"));
+        assert!(!is_declaration_keyword("param"));
+    }
+
+    #[test]
+    fn finds_workflow_bodies_until_the_matching_brace() {
+        let input = "workflow Test {\n    parallel { @{ a = 1 } }\n}\nparallel\n";
+        let tokens = tokenize(input);
+        let bodies = workflow_bodies(&tokens, input);
+
+        assert_eq!(bodies.len(), 1);
+        let (open, close) = bodies[0];
+        assert_eq!(tokens[open].line, 1);
+        assert_eq!(tokens[close].line, 3);
+
+        let unclosed = "workflow Test {\n    parallel\n";
+        let tokens = tokenize(unclosed);
+        assert_eq!(workflow_bodies(&tokens, unclosed), vec![(4, tokens.len())]);
+    }
+
+    #[test]
+    fn member_declarations_cover_methods_constructors_and_enum_values() {
+        let input = "class Job : Base, IDisposable\n{\n    [string] $Name\n    Job() { }\n    [void] Process([int] $x) { ls; Get-Item (Join-Path a b) }\n    static [int] End() { return 1 }\n}\nenum Stage { Begin; Process = 2 }\nclass\nProcess()\n";
+
+        assert_eq!(
+            member_names(input),
+            vec!["Job", "Process", "End", "Begin", "Process"]
+        );
+    }
+}
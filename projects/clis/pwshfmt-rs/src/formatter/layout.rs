@@ -0,0 +1,1076 @@
+use crate::config::IndentStyle;
+
+use super::keywords;
+use super::lexer::{TokenKind, tokenize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutOptions {
+    pub indent_style: IndentStyle,
+    pub indent_width: usize,
+    pub align_assignments: bool,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            indent_style: IndentStyle::Space,
+            indent_width: 4,
+            align_assignments: true,
+        }
+    }
+}
+
+// 大小写修正阶段记录的 token 所处位置：命令参数中的 `a=b`、`*.ps1` 不能按运算符补空格。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenContext {
+    Code,
+    CommandArgument,
+    NativeArgument,
+    // 含动态调用的语句，排版阶段不做任何改动。
+    Verbatim,
+}
+
+#[derive(Debug, Clone)]
+struct Piece {
+    kind: TokenKind,
+    text: String,
+    context: TokenContext,
+}
+
+// 对应 PSScriptAnalyzer CodeFormatting 预设：OTBS 大括号、缩进、运算符/管道/分隔符空格与哈希表赋值对齐。
+pub fn apply(input: &str, contexts: &[TokenContext], options: &LayoutOptions) -> (String, usize) {
+    let tokens = tokenize(input);
+    // 大小写修正不改变 token 划分；对不上时按原生参数处理，只做不改变语义的调整。
+    let aligned = tokens.len() == contexts.len();
+    if !aligned && contexts.contains(&TokenContext::Verbatim) {
+        return (input.to_string(), 0);
+    }
+    let pieces = tokens
+        .iter()
+        .enumerate()
+        .map(|(index, token)| Piece {
+            kind: token.kind,
+            text: token.text(input).to_string(),
+            context: if aligned {
+                contexts[index]
+            } else {
+                TokenContext::NativeArgument
+            },
+        })
+        .collect();
+
+    let mut layout = Layout {
+        pieces,
+        options,
+        newline: detect_newline(input),
+        fixes: 0,
+    };
+    layout.place_braces();
+    layout.space_tokens();
+    layout.indent_lines();
+    if options.align_assignments {
+        layout.align_hashtables();
+    }
+
+    (layout.render(), layout.fixes)
+}
+
+fn is_opener(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::LParen
+            | TokenKind::SubExpressionStart
+            | TokenKind::ArrayExpressionStart
+            | TokenKind::LBrace
+            | TokenKind::HashtableStart
+            | TokenKind::LBracket
+    )
+}
+
+fn is_closer(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::RParen | TokenKind::RBrace | TokenKind::RBracket
+    )
+}
+
+fn detect_newline(input: &str) -> &'static str {
+    if input.contains("\r\n") { "\r\n" } else { "\n" }
+}
+
+struct Layout<'a> {
+    pieces: Vec<Piece>,
+    options: &'a LayoutOptions,
+    newline: &'static str,
+    fixes: usize,
+}
+
+const CONDITION_KEYWORDS: [&str; 7] =
+    ["if", "elseif", "foreach", "for", "while", "switch", "until"];
+
+const BLOCK_KEYWORDS: [&str; 9] = [
+    "else",
+    "try",
+    "finally",
+    "do",
+    "begin",
+    "process",
+    "end",
+    "clean",
+    "dynamicparam",
+];
+
+const CONTINUATION_KEYWORDS: [&str; 4] = ["else", "elseif", "catch", "finally"];
+
+impl Layout<'_> {
+    fn render(&self) -> String {
+        self.pieces
+            .iter()
+            .map(|piece| piece.text.as_str())
+            .collect()
+    }
+
+    fn kind(&self, index: usize) -> Option<TokenKind> {
+        self.pieces.get(index).map(|piece| piece.kind)
+    }
+
+    fn is_word(&self, index: usize, candidates: &[&str]) -> bool {
+        self.pieces.get(index).is_some_and(|piece| {
+            piece.kind == TokenKind::Word
+                && candidates
+                    .iter()
+                    .any(|candidate| candidate.eq_ignore_ascii_case(&piece.text))
+        })
+    }
+
+    fn close_of(&self, open: usize) -> Option<usize> {
+        let mut depth = 0usize;
+        for index in open..self.pieces.len() {
+            if is_opener(self.pieces[index].kind) {
+                depth += 1;
+            } else if is_closer(self.pieces[index].kind) {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+        }
+        None
+    }
+
+    fn open_of(&self, close: usize) -> Option<usize> {
+        let mut depth = 0usize;
+        for index in (0..=close).rev() {
+            if is_closer(self.pieces[index].kind) {
+                depth += 1;
+            } else if is_opener(self.pieces[index].kind) {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+        }
+        None
+    }
+
+    fn matching(&self) -> Vec<Option<usize>> {
+        let mut matches = vec![None; self.pieces.len()];
+        let mut stack: Vec<usize> = Vec::new();
+        for (index, piece) in self.pieces.iter().enumerate() {
+            if is_opener(piece.kind) {
+                stack.push(index);
+            } else if is_closer(piece.kind)
+                && let Some(open) = stack.pop()
+            {
+                matches[open] = Some(index);
+                matches[index] = Some(open);
+            }
+        }
+        matches
+    }
+
+    // 向前跳过空白（可选跳过换行），遇到注释即停止，避免把注释挪到别的行。
+    fn previous(&self, index: usize, skip_newlines: bool) -> Option<usize> {
+        (0..index).rev().find(|&candidate| {
+            let kind = self.pieces[candidate].kind;
+            !(kind == TokenKind::Whitespace || (skip_newlines && kind == TokenKind::NewLine))
+        })
+    }
+
+    fn next(&self, index: usize, skip_newlines: bool) -> Option<usize> {
+        (index + 1..self.pieces.len()).find(|&candidate| {
+            let kind = self.pieces[candidate].kind;
+            !(kind == TokenKind::Whitespace || (skip_newlines && kind == TokenKind::NewLine))
+        })
+    }
+
+    fn spans_lines(&self, open: usize, close: usize) -> bool {
+        self.pieces[open..close]
+            .iter()
+            .any(|piece| piece.kind == TokenKind::NewLine)
+    }
+
+    fn replace_range(&mut self, start: usize, end: usize, text: &str, kind: TokenKind) {
+        let context = self.pieces[start.min(self.pieces.len() - 1)].context;
+        self.pieces.splice(
+            start..end,
+            std::iter::once(Piece {
+                kind,
+                text: text.to_string(),
+                context,
+            }),
+        );
+        self.fixes += 1;
+    }
+
+    fn place_braces(&mut self) {
+        let mut index = 0;
+        while index < self.pieces.len() {
+            if matches!(
+                self.pieces[index].context,
+                TokenContext::NativeArgument | TokenContext::Verbatim
+            ) {
+                index += 1;
+                continue;
+            }
+            match self.pieces[index].kind {
+                TokenKind::LBrace => {
+                    index = self.join_open_brace(index);
+                    self.break_after_open_brace(index);
+                }
+                TokenKind::RBrace => {
+                    index = self.break_before_close_brace(index);
+                    self.join_continuation(index);
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+    }
+
+    // `if ($x)\n{` → `if ($x) {`
+    // 返回 `{` 的新下标。
+    fn join_open_brace(&mut self, index: usize) -> usize {
+        let Some(previous) = self.previous(index, true) else {
+            return index;
+        };
+        if !self.pieces[previous + 1..index]
+            .iter()
+            .any(|piece| piece.kind == TokenKind::NewLine)
+            || !self.owns_block(previous)
+        {
+            return index;
+        }
+
+        self.replace_range(previous + 1, index, " ", TokenKind::Whitespace);
+        previous + 2
+    }
+
+    // 只有语句关键字（或其条件括号、函数声明）后的脚本块才能安全上移，普通命令后的 `{` 是独立语句。
+    fn owns_block(&self, index: usize) -> bool {
+        if self.is_word(index, &BLOCK_KEYWORDS) || self.is_word(index, &["catch"]) {
+            return true;
+        }
+        if self.kind(index) == Some(TokenKind::Word) {
+            return self.follows_declaration(index);
+        }
+
+        let close_kind = self.kind(index);
+        if close_kind != Some(TokenKind::RParen) && close_kind != Some(TokenKind::RBracket) {
+            return false;
+        }
+        let Some(open) = self.open_of(index) else {
+            return false;
+        };
+
+        let mut owner = self.previous(open, false);
+        while let Some(candidate) = owner {
+            if self.kind(candidate) == Some(TokenKind::Parameter) {
+                owner = self.previous(candidate, false);
+                continue;
+            }
+            break;
+        }
+        owner.is_some_and(|owner| {
+            self.is_word(owner, &CONDITION_KEYWORDS)
+                || self.is_word(owner, &["catch"])
+                || (self.kind(owner) == Some(TokenKind::Word) && self.follows_declaration(owner))
+        })
+    }
+
+    fn follows_declaration(&self, index: usize) -> bool {
+        self.previous(index, false).is_some_and(|previous| {
+            self.kind(previous) == Some(TokenKind::Word)
+                && keywords::is_declaration_keyword(&self.pieces[previous].text)
+        })
+    }
+
+    // 多行脚本块的 `{` 之后直接换行。
+    fn break_after_open_brace(&mut self, index: usize) {
+        let Some(close) = self.close_of(index) else {
+            return;
+        };
+        let Some(next) = self.next(index, false) else {
+            return;
+        };
+        if next == close
+            || !self.spans_lines(index, close)
+            || matches!(
+                self.pieces[next].kind,
+                TokenKind::NewLine | TokenKind::Comment | TokenKind::LineContinuation
+            )
+        {
+            return;
+        }
+
+        let newline = self.newline;
+        self.replace_range(index + 1, next, newline, TokenKind::NewLine);
+    }
+
+    // 多行块的 `}` 独占一行；返回 `}` 的新下标。
+    fn break_before_close_brace(&mut self, index: usize) -> usize {
+        let Some(open) = self.open_of(index) else {
+            return index;
+        };
+        let Some(previous) = self.previous(index, false) else {
+            return index;
+        };
+        if previous == open
+            || !self.spans_lines(open, index)
+            || self.is_inline_hashtable(open)
+            || matches!(
+                self.pieces[previous].kind,
+                TokenKind::NewLine | TokenKind::LineContinuation
+            )
+        {
+            return index;
+        }
+
+        let newline = self.newline;
+        self.replace_range(previous + 1, index, newline, TokenKind::NewLine);
+        previous + 2
+    }
+
+    // `}\nelse {` → `} else {`
+    fn join_continuation(&mut self, index: usize) {
+        let Some(next) = self.next(index, true) else {
+            return;
+        };
+        // 与 PSSA 的 IgnoreOneLineBlock 一致：`if ($a) { 1 }` 换行接 `else { 2 }` 保持原样。
+        if !self.is_word(next, &CONTINUATION_KEYWORDS)
+            || self
+                .open_of(index)
+                .is_none_or(|open| !self.spans_lines(open, index))
+            || !self.pieces[index + 1..next]
+                .iter()
+                .any(|piece| piece.kind == TokenKind::NewLine)
+        {
+            return;
+        }
+
+        self.replace_range(index + 1, next, " ", TokenKind::Whitespace);
+    }
+
+    fn space_tokens(&mut self) {
+        // 方括号内是类型名或下标（`[Environment+SpecialFolder]`、`$a[$i-1]`），不调整运算符空格。
+        let mut brackets: Vec<bool> = Vec::new();
+        let mut index = 0;
+        while index < self.pieces.len() {
+            let kind = self.pieces[index].kind;
+            if is_opener(kind) {
+                brackets.push(kind == TokenKind::LBracket);
+            } else if is_closer(kind) {
+                brackets.pop();
+            }
+            let in_brackets = brackets.last() == Some(&true);
+            if self.pieces[index].context == TokenContext::Verbatim {
+                index += 1;
+                continue;
+            }
+
+            let native = self.pieces[index].context == TokenContext::NativeArgument;
+            match kind {
+                TokenKind::LBrace if !native => {
+                    if self
+                        .previous(index, false)
+                        .is_some_and(|previous| self.spaces_before_brace(previous))
+                    {
+                        index = self.ensure_space_before(index);
+                    }
+                    let bareword = index > 0
+                        && self.pieces[index - 1].kind == TokenKind::Word
+                        && self.pieces[index - 1].context != TokenContext::Code;
+                    if !bareword {
+                        self.pad_inner_braces(index);
+                    }
+                }
+                // `}catch {` → `} catch {`
+                TokenKind::RBrace
+                    if !native
+                        && self
+                            .next(index, false)
+                            .is_some_and(|next| self.is_word(next, &CONTINUATION_KEYWORDS)) =>
+                {
+                    self.ensure_space_after(index);
+                }
+                TokenKind::HashtableStart if !native => self.pad_inner_braces(index),
+                TokenKind::LParen
+                    if self
+                        .previous(index, false)
+                        .is_some_and(|previous| self.is_word(previous, &CONDITION_KEYWORDS)) =>
+                {
+                    index = self.ensure_space_before(index);
+                }
+                TokenKind::Pipe => {
+                    index = self.ensure_space_before(index);
+                    self.ensure_space_after(index);
+                }
+                // `$x = ,$item` 中的一元逗号保持原样。
+                TokenKind::Comma | TokenKind::Semicolon
+                    if !native
+                        && (self.pieces[index].kind == TokenKind::Semicolon
+                            || self.follows_operand(index)) =>
+                {
+                    index = self.remove_space_before(index);
+                    if !matches!(
+                        self.kind(index + 1),
+                        Some(TokenKind::RParen | TokenKind::RBrace)
+                    ) {
+                        self.ensure_space_after(index);
+                    }
+                }
+                TokenKind::Operator | TokenKind::NamedOperator
+                    if !in_brackets && self.is_binary_operator(index) =>
+                {
+                    index = self.ensure_space_before(index);
+                    self.ensure_space_after(index);
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+    }
+
+    // `try{`、`function Foo{`、`ForEach-Object{`、`-Process{` 补空格；运算符、分隔符与左括号后不补。
+    // 命令参数中的裸词 `a{b}`、方法调用 `$items.foreach{}` 与类型转换 `[scriptblock]{}` 保持原样。
+    fn spaces_before_brace(&self, previous: usize) -> bool {
+        let piece = &self.pieces[previous];
+        match piece.kind {
+            TokenKind::RParen | TokenKind::Parameter => true,
+            TokenKind::Word
+                if previous > 0
+                    && matches!(
+                        self.pieces[previous - 1].kind,
+                        TokenKind::Dot | TokenKind::DoubleColon
+                    ) =>
+            {
+                false
+            }
+            TokenKind::Operator
+            | TokenKind::NamedOperator
+            | TokenKind::Pipe
+            | TokenKind::Comma
+            | TokenKind::Semicolon
+            | TokenKind::Ampersand
+            | TokenKind::Dot
+            | TokenKind::DoubleColon
+            | TokenKind::RBracket
+            | TokenKind::NewLine
+            | TokenKind::LineContinuation
+            | TokenKind::Comment => false,
+            kind => !is_opener(kind) && piece.context == TokenContext::Code,
+        }
+    }
+
+    fn is_binary_operator(&self, index: usize) -> bool {
+        let piece = &self.pieces[index];
+        if piece.context != TokenContext::Code {
+            return false;
+        }
+
+        let text = piece.text.to_ascii_lowercase();
+        let spaced = match piece.kind {
+            TokenKind::Operator => matches!(
+                text.as_str(),
+                "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "??=" | "+" | "-" | "*" | "/" | "%"
+            ),
+            TokenKind::NamedOperator => !matches!(text.as_str(), "-not" | "-bnot"),
+            _ => false,
+        };
+
+        // 一元 `-1`、`-join $parts` 等前面没有操作数，不补空格。
+        spaced && self.follows_operand(index)
+    }
+
+    fn follows_operand(&self, index: usize) -> bool {
+        let Some(previous) = self.previous(index, false) else {
+            return false;
+        };
+        match self.pieces[previous].kind {
+            // 哈希表中的 `key=value`：行首或分号后的裸词是键，不是命令名。
+            TokenKind::Word
+                if self.pieces[index].kind == TokenKind::Operator
+                    && self.pieces[index].text == "="
+                    && self.enclosing_group(index) == Some(TokenKind::HashtableStart) =>
+            {
+                true
+            }
+            // `return ,$x`、`Write-Output ,$x` 中的关键字或命令名不是操作数。
+            TokenKind::Word => {
+                keywords::keyword(&self.pieces[previous].text).is_none()
+                    && (self.pieces[previous].context != TokenContext::Code
+                        || !self.previous(previous, false).is_none_or(|before| {
+                            matches!(
+                                self.pieces[before].kind,
+                                TokenKind::NewLine
+                                    | TokenKind::Pipe
+                                    | TokenKind::Semicolon
+                                    | TokenKind::LBrace
+                                    | TokenKind::LParen
+                                    | TokenKind::SubExpressionStart
+                                    | TokenKind::Ampersand
+                            )
+                        }))
+            }
+            kind => matches!(
+                kind,
+                TokenKind::Variable
+                    | TokenKind::Number
+                    | TokenKind::StringLiteral
+                    | TokenKind::HereStringLiteral
+                    | TokenKind::ExpandableString
+                    | TokenKind::ExpandableHereString
+                    | TokenKind::RParen
+                    | TokenKind::RBracket
+                    | TokenKind::RBrace
+            ),
+        }
+    }
+
+    fn enclosing_group(&self, index: usize) -> Option<TokenKind> {
+        let mut depth = 0usize;
+        for candidate in (0..index).rev() {
+            let kind = self.pieces[candidate].kind;
+            if is_closer(kind) {
+                depth += 1;
+            } else if is_opener(kind) {
+                if depth == 0 {
+                    return Some(kind);
+                }
+                depth -= 1;
+            }
+        }
+        None
+    }
+
+    // 返回操作后原 token 的新下标。
+    fn ensure_space_before(&mut self, index: usize) -> usize {
+        if index == 0 {
+            return index;
+        }
+        match self.pieces[index - 1].kind {
+            TokenKind::Whitespace => {
+                let line_start = index < 2
+                    || matches!(
+                        self.pieces[index - 2].kind,
+                        TokenKind::NewLine | TokenKind::LineContinuation
+                    );
+                if !line_start && self.pieces[index - 1].text != " " {
+                    self.pieces[index - 1].text = " ".to_string();
+                    self.fixes += 1;
+                }
+                index
+            }
+            TokenKind::NewLine | TokenKind::LineContinuation => index,
+            _ => {
+                self.insert_space(index);
+                index + 1
+            }
+        }
+    }
+
+    fn ensure_space_after(&mut self, index: usize) {
+        match self.kind(index + 1) {
+            None | Some(TokenKind::NewLine | TokenKind::LineContinuation) => {}
+            Some(TokenKind::Whitespace) => {
+                let line_end = matches!(
+                    self.kind(index + 2),
+                    None | Some(
+                        TokenKind::NewLine | TokenKind::LineContinuation | TokenKind::Comment
+                    )
+                );
+                if !line_end && self.pieces[index + 1].text != " " {
+                    self.pieces[index + 1].text = " ".to_string();
+                    self.fixes += 1;
+                }
+            }
+            Some(_) => self.insert_space(index + 1),
+        }
+    }
+
+    fn remove_space_before(&mut self, index: usize) -> usize {
+        if index < 2 || self.pieces[index - 1].kind != TokenKind::Whitespace {
+            return index;
+        }
+
+        self.pieces.remove(index - 1);
+        self.fixes += 1;
+        index - 1
+    }
+
+    fn insert_space(&mut self, index: usize) {
+        let context = self.pieces[index.min(self.pieces.len() - 1)].context;
+        self.pieces.insert(
+            index,
+            Piece {
+                kind: TokenKind::Whitespace,
+                text: " ".to_string(),
+                context,
+            },
+        );
+        self.fixes += 1;
+    }
+
+    // 单行块 `{x}` → `{ x }`。
+    fn pad_inner_braces(&mut self, index: usize) {
+        let Some(close) = self.close_of(index) else {
+            return;
+        };
+        if close == index + 1
+            || self.spans_lines(index, close)
+            || self.pieces[index + 1..close]
+                .iter()
+                .all(|piece| piece.kind == TokenKind::Whitespace)
+        {
+            return;
+        }
+
+        match self.pieces[close - 1].kind {
+            TokenKind::Whitespace if self.pieces[close - 1].text != " " => {
+                self.pieces[close - 1].text = " ".to_string();
+                self.fixes += 1;
+            }
+            TokenKind::Whitespace => {}
+            _ => self.insert_space(close),
+        }
+        self.ensure_space_after(index);
+    }
+
+    fn indent_lines(&mut self) {
+        let unit = match self.options.indent_style {
+            IndentStyle::Space => " ".repeat(self.options.indent_width),
+            IndentStyle::Tab => "\t".to_string(),
+        };
+
+        let mut depth = 0usize;
+        let mut groups: Vec<TokenKind> = Vec::new();
+        let mut pipelines: Vec<usize> = Vec::new();
+        let mut index = 0;
+        let mut at_line_start = true;
+
+        while index < self.pieces.len() {
+            if at_line_start {
+                at_line_start = false;
+                index = self.indent_line(index, depth, &mut pipelines, &unit);
+                continue;
+            }
+
+            let kind = self.pieces[index].kind;
+            if is_opener(kind) {
+                groups.push(kind);
+            } else if is_closer(kind) {
+                groups.pop();
+            }
+            match kind {
+                TokenKind::LParen
+                | TokenKind::SubExpressionStart
+                | TokenKind::ArrayExpressionStart
+                | TokenKind::LBrace
+                | TokenKind::HashtableStart => depth += 1,
+                TokenKind::RParen | TokenKind::RBrace => {
+                    depth = depth.saturating_sub(1);
+                    pipelines.retain(|level| *level <= depth);
+                }
+                // 以运算符结尾的行、括号外以逗号结尾的行由下一行续写，保留续行的原有缩进；
+                // 括号内逗号分隔的列表（如多行 `param()`）按括号深度缩进。
+                TokenKind::NewLine
+                    if self.previous(index, false).is_some_and(|previous| {
+                        match self.pieces[previous].kind {
+                            TokenKind::Operator | TokenKind::NamedOperator => true,
+                            TokenKind::Comma => !groups.last().is_some_and(|group| {
+                                matches!(
+                                    group,
+                                    TokenKind::LParen
+                                        | TokenKind::SubExpressionStart
+                                        | TokenKind::ArrayExpressionStart
+                                        | TokenKind::LBracket
+                                )
+                            }),
+                            _ => false,
+                        }
+                    }) => {}
+                TokenKind::NewLine => {
+                    let continued = self
+                        .previous(index, false)
+                        .is_some_and(|previous| self.kind(previous) == Some(TokenKind::Pipe))
+                        || self
+                            .next(index, true)
+                            .is_some_and(|next| self.kind(next) == Some(TokenKind::Pipe));
+                    if continued {
+                        if pipelines.last() != Some(&depth) {
+                            pipelines.push(depth);
+                        }
+                    } else {
+                        pipelines.retain(|level| *level < depth);
+                    }
+                    at_line_start = true;
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+    }
+
+    // 返回本行首个内容 token 的下标，由调用方继续统计括号深度。
+    fn indent_line(
+        &mut self,
+        start: usize,
+        depth: usize,
+        pipelines: &mut [usize],
+        unit: &str,
+    ) -> usize {
+        let has_whitespace = self.kind(start) == Some(TokenKind::Whitespace);
+        let content = if has_whitespace { start + 1 } else { start };
+        // 保留区域内部的续行保持原样。
+        if start > 0 && self.pieces[start - 1].context == TokenContext::Verbatim {
+            return content;
+        }
+
+        if matches!(self.kind(content), None | Some(TokenKind::NewLine)) {
+            if has_whitespace {
+                self.pieces.remove(start);
+                self.fixes += 1;
+                return start;
+            }
+            return content;
+        }
+
+        // 与 PSSA 一致：行首连续的 `})` 只回退一级，对应同一行连续开启的 `(@{` 只按行首计算。
+        let closes = matches!(
+            self.kind(content),
+            Some(TokenKind::RParen | TokenKind::RBrace)
+        );
+        let level = depth.saturating_sub(usize::from(closes));
+        let extra = pipelines
+            .iter()
+            .filter(|pipeline| **pipeline <= level)
+            .count();
+        let desired = unit.repeat(level + extra);
+
+        if has_whitespace {
+            if self.pieces[start].text != desired {
+                self.pieces[start].text = desired;
+                self.fixes += 1;
+            }
+            content
+        } else if desired.is_empty() {
+            content
+        } else {
+            let context = self.pieces[start].context;
+            self.pieces.insert(
+                start,
+                Piece {
+                    kind: TokenKind::Whitespace,
+                    text: desired,
+                    context,
+                },
+            );
+            self.fixes += 1;
+            content + 1
+        }
+    }
+
+    // 多行哈希表中每行一个的 `key = value` 对齐等号（PSAlignAssignmentStatement）。
+    fn align_hashtables(&mut self) {
+        let hashtables: Vec<(usize, usize)> = self
+            .matching()
+            .into_iter()
+            .enumerate()
+            .filter(|(open, _)| self.pieces[*open].kind == TokenKind::HashtableStart)
+            .filter_map(|(open, close)| Some((open, close?)))
+            .collect();
+        for (open, close) in hashtables {
+            let entries = self.hashtable_entries(open, close);
+            if entries.len() < 2
+                || self.is_inline_hashtable(open)
+                || entries.iter().any(|(whitespace, _)| {
+                    self.pieces[*whitespace].context == TokenContext::Verbatim
+                })
+            {
+                continue;
+            }
+            let width = entries
+                .iter()
+                .map(|(_, key_width)| *key_width)
+                .max()
+                .unwrap_or(0);
+            for (whitespace, key_width) in entries {
+                let padding = " ".repeat(width - key_width + 1);
+                if self.pieces[whitespace].text != padding {
+                    self.pieces[whitespace].text = padding;
+                    self.fixes += 1;
+                }
+            }
+        }
+    }
+
+    // `@{a=1\nb=2}` 第一项与 `@{` 同行，无法与后续各行对齐，保留原有的行结构。
+    fn is_inline_hashtable(&self, open: usize) -> bool {
+        self.pieces[open].kind == TokenKind::HashtableStart
+            && self.next(open, false).is_some_and(|next| {
+                !matches!(
+                    self.pieces[next].kind,
+                    TokenKind::NewLine | TokenKind::Comment | TokenKind::RBrace
+                )
+            })
+    }
+
+    // 返回（`=` 前空白的下标, 键宽度）；键与 `=` 之间没有空白的条目已在运算符空格阶段补齐。
+    fn hashtable_entries(&self, open: usize, close: usize) -> Vec<(usize, usize)> {
+        let mut entries = Vec::new();
+        let mut depth = 0usize;
+        for index in open + 1..close {
+            match self.pieces[index].kind {
+                TokenKind::LParen
+                | TokenKind::SubExpressionStart
+                | TokenKind::ArrayExpressionStart
+                | TokenKind::LBrace
+                | TokenKind::HashtableStart
+                | TokenKind::LBracket => depth += 1,
+                TokenKind::RParen | TokenKind::RBrace | TokenKind::RBracket => {
+                    depth = depth.saturating_sub(1);
+                }
+                TokenKind::NewLine if depth == 0 => {
+                    if let Some(entry) = self.hashtable_entry(index + 1) {
+                        entries.push(entry);
+                    }
+                }
+                _ => {}
+            }
+        }
+        entries
+    }
+
+    // 键可以是裸词、字符串或 `[Environment]::GetEnvironmentVariable('X')` 这类表达式，取本行第一个顶层 `=`。
+    fn hashtable_entry(&self, line_start: usize) -> Option<(usize, usize)> {
+        let key = if self.kind(line_start) == Some(TokenKind::Whitespace) {
+            line_start + 1
+        } else {
+            line_start
+        };
+        if matches!(
+            self.kind(key),
+            None | Some(TokenKind::NewLine | TokenKind::Comment | TokenKind::RBrace)
+        ) {
+            return None;
+        }
+
+        let mut depth = 0usize;
+        for index in key..self.pieces.len() {
+            let piece = &self.pieces[index];
+            match piece.kind {
+                TokenKind::NewLine | TokenKind::LineContinuation => return None,
+                kind if is_opener(kind) => depth += 1,
+                kind if is_closer(kind) => depth = depth.checked_sub(1)?,
+                TokenKind::Operator if depth == 0 && piece.text == "=" => {
+                    if index == key || self.pieces[index - 1].kind != TokenKind::Whitespace {
+                        return None;
+                    }
+                    let width = self.pieces[key..index - 1]
+                        .iter()
+                        .map(|piece| piece.text.chars().count())
+                        .sum();
+                    return Some((index - 1, width));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LayoutOptions, TokenContext, apply};
+    use crate::config::IndentStyle;
+    use crate::formatter::lexer::tokenize;
+
+    fn layout(input: &str) -> String {
+        layout_with(input, &LayoutOptions::default())
+    }
+
+    fn layout_with(input: &str, options: &LayoutOptions) -> String {
+        let contexts = vec![TokenContext::Code; tokenize(input).len()];
+        apply(input, &contexts, options).0
+    }
+
+    #[test]
+    fn places_braces_one_true_brace_style() {
+        let input = "if ($a)\n{\n$b\n}\nelse\n{ $c\n$d }\nfunction Get-Foo\n{\n}\n";
+
+        assert_eq!(
+            layout(input),
+            "if ($a) {\n    $b\n} else {\n    $c\n    $d\n}\nfunction Get-Foo {\n}\n"
+        );
+    }
+
+    #[test]
+    fn keeps_one_line_blocks_before_else_on_separate_lines() {
+        let input = "if ($a) { 1 }\nelse { 2 }\n";
+
+        assert_eq!(layout(input), input);
+    }
+
+    #[test]
+    fn keeps_standalone_script_blocks_on_their_own_line() {
+        let input = "Write-Output (1)\n{\n    $b\n}\n";
+
+        assert_eq!(layout(input), input);
+    }
+
+    #[test]
+    fn normalizes_whitespace_around_operators_pipes_and_separators() {
+        let input = "$a=1+2\nif($a -eq  1){$b}\n$list=@(1 ,2,3)|Sort-Object\n$n = -1; $m = ,$x\nreturn ,$m\n[Environment+SpecialFolder]\n";
+
+        assert_eq!(
+            layout(input),
+            "$a = 1 + 2\nif ($a -eq 1) { $b }\n$list = @(1, 2, 3) | Sort-Object\n$n = -1; $m = ,$x\nreturn ,$m\n[Environment+SpecialFolder]\n"
+        );
+    }
+
+    #[test]
+    fn spaces_braces_after_keywords_names_and_before_continuations() {
+        let input = "try{ $a }catch{ $b }finally{ $c }\nif ($a){ 1 }elseif ($b){ 2 }else{ 3 }\nfunction Foo{ $x }\nGet-ChildItem | ForEach-Object{ $_ }\n$sb = @{ a = { 1 } }\n$items.foreach{ $_ }\n[scriptblock]{ 1 }\n";
+
+        assert_eq!(
+            layout(input),
+            "try { $a } catch { $b } finally { $c }\nif ($a) { 1 } elseif ($b) { 2 } else { 3 }\nfunction Foo { $x }\nGet-ChildItem | ForEach-Object { $_ }\n$sb = @{ a = { 1 } }\n$items.foreach{ $_ }\n[scriptblock]{ 1 }\n"
+        );
+    }
+
+    #[test]
+    fn leaves_command_arguments_alone() {
+        let input = "git config user.name=foo\nGet-ChildItem *.ps1\n";
+        let contexts: Vec<TokenContext> = tokenize(input)
+            .iter()
+            .map(|token| {
+                if token.column == 1 {
+                    TokenContext::Code
+                } else if token.line == 1 {
+                    TokenContext::NativeArgument
+                } else {
+                    TokenContext::CommandArgument
+                }
+            })
+            .collect();
+
+        assert_eq!(apply(input, &contexts, &LayoutOptions::default()).0, input);
+    }
+
+    #[test]
+    fn indents_blocks_and_continued_pipelines() {
+        let input = "function Get-Foo {\nparam(\n$Path\n)\nGet-ChildItem |\nForEach-Object {\n$_\n}\n\n  \n}\n";
+
+        assert_eq!(
+            layout(input),
+            "function Get-Foo {\n    param(\n        $Path\n    )\n    Get-ChildItem |\n        ForEach-Object {\n            $_\n        }\n\n\n}\n"
+        );
+    }
+
+    #[test]
+    fn indents_comma_separated_lists_inside_groups() {
+        let input = "function Get-Foo {\nparam(\n[string]$Path,\n[int]$Count,\n[switch]$Force\n)\n$list = @(1,\n2)\n$pair = 1,\n        2\n}\n";
+
+        assert_eq!(
+            layout(input),
+            "function Get-Foo {\n    param(\n        [string]$Path,\n        [int]$Count,\n        [switch]$Force\n    )\n    $list = @(1,\n        2)\n    $pair = 1,\n        2\n}\n"
+        );
+    }
+
+    #[test]
+    fn dedents_leading_closers_once_per_line() {
+        let input = "$list.Add(@{\nName = 1\n})\n$sum = 1 +\n        2\n";
+
+        assert_eq!(
+            layout(input),
+            "$list.Add(@{\n        Name = 1\n    })\n$sum = 1 +\n        2\n"
+        );
+    }
+
+    #[test]
+    fn indents_with_tabs_when_configured() {
+        let options = LayoutOptions {
+            indent_style: IndentStyle::Tab,
+            ..LayoutOptions::default()
+        };
+
+        assert_eq!(
+            layout_with("if ($a) {\n  $b\n}\n", &options),
+            "if ($a) {\n\t$b\n}\n"
+        );
+    }
+
+    #[test]
+    fn aligns_hashtable_assignments() {
+        let input = "$h = @{\n    Name = 'a'\n    LongerKey = 'b'\n    Nested = @{\n        X = 1\n    }\n}\n$m = @{\n    [IO.Path]::Combine('a') = 1\n    'b' = 2\n}\n";
+
+        assert_eq!(
+            layout(input),
+            "$h = @{\n    Name      = 'a'\n    LongerKey = 'b'\n    Nested    = @{\n        X = 1\n    }\n}\n$m = @{\n    [IO.Path]::Combine('a') = 1\n    'b'                     = 2\n}\n"
+        );
+    }
+
+    #[test]
+    fn spaces_and_aligns_unspaced_hashtable_keys() {
+        let input = "$h = @{\na=1\nlonger=2\n}\n$i = @{a=1;b=2}\n";
+
+        assert_eq!(
+            layout(input),
+            "$h = @{\n    a      = 1\n    longer = 2\n}\n$i = @{ a = 1; b = 2 }\n"
+        );
+    }
+
+    #[test]
+    fn leaves_hashtables_starting_on_the_open_line_unaligned() {
+        let input = "$h = @{a=1\nbbb=2}\n$i = @{ a = 1\n    bbb = 2\n    cc = 3\n}\n";
+
+        assert_eq!(
+            layout(input),
+            "$h = @{a = 1\n    bbb = 2}\n$i = @{ a = 1\n    bbb = 2\n    cc = 3\n}\n"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_safe_adjustments_when_contexts_do_not_match() {
+        let input = "if ($a)\n{\n$b=1\n}\n";
+
+        assert_eq!(
+            apply(input, &[TokenContext::Code], &LayoutOptions::default()).0,
+            "if ($a)\n{\n    $b=1\n}\n"
+        );
+        assert_eq!(
+            apply(input, &[TokenContext::Verbatim], &LayoutOptions::default()),
+            (input.to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn keeps_here_string_bodies_untouched() {
+        let input = "if ($a) {\n$s = @\"\n  raw=text\n\"@\n}\n";
+
+        assert_eq!(
+            layout(input),
+            "if ($a) {\n    $s = @\"\n  raw=text\n\"@\n}\n"
+        );
+    }
+}
@@ -0,0 +1,682 @@
+use super::operators;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    NewLine,
+    LineContinuation,
+    Comment,
+    BlockComment,
+    StringLiteral,
+    HereStringLiteral,
+    ExpandableString,
+    ExpandableHereString,
+    Variable,
+    SplattedVariable,
+    Number,
+    Parameter,
+    NamedOperator,
+    StopParsing,
+    Word,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    SubExpressionStart,
+    ArrayExpressionStart,
+    HashtableStart,
+    Semicolon,
+    Comma,
+    Pipe,
+    Ampersand,
+    Dot,
+    DoubleColon,
+    Operator,
+    Unknown,
+}
+
+impl TokenKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            Self::Whitespace | Self::LineContinuation | Self::Comment | Self::BlockComment
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Token {
+    pub fn text<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.start..self.end]
+    }
+}
+
+const OPERATORS: [&str; 24] = [
+    "??=", "&&", "||", "??", "+=", "-=", "*=", "/=", "%=", "++", "--", "..", ">>", "=", "+", "-",
+    "*", "/", "%", "!", ">", "<", "?", ":",
+];
+
+pub fn tokenize(input: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(input);
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Code,
+    SubExpression { paren_depth: usize },
+    ExpandableString,
+    ExpandableHereString,
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    cursor: usize,
+    line: usize,
+    column: usize,
+    modes: Vec<Mode>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            cursor: 0,
+            line: 1,
+            column: 1,
+            modes: vec![Mode::Code],
+        }
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let character = self.peek()?;
+        let start = self.cursor;
+        let line = self.line;
+        let column = self.column;
+
+        let kind = match self.mode() {
+            Mode::ExpandableString => self.scan_expandable_fragment(false),
+            Mode::ExpandableHereString => self.scan_expandable_fragment(true),
+            Mode::Code | Mode::SubExpression { .. } => {
+                let depth = self.modes.len();
+                let kind = self.scan_code(character);
+                // `"$(` 会在同一个 token 内进入字符串再进入子表达式，新压入的层不能再计数。
+                if self.modes.len() == depth {
+                    self.track_sub_expression_depth(kind);
+                }
+                kind
+            }
+        };
+
+        Some(Token {
+            kind,
+            start,
+            end: self.cursor,
+            line,
+            column,
+        })
+    }
+
+    fn scan_code(&mut self, character: char) -> TokenKind {
+        if self.starts_with("\r\n") || character == '\n' {
+            self.advance_str(if character == '\r' { "\r\n" } else { "\n" });
+            return TokenKind::NewLine;
+        }
+
+        if character == ' ' || character == '\t' || character == '\u{feff}' {
+            self.advance_while(|value| value == ' ' || value == '\t' || value == '\u{feff}');
+            return TokenKind::Whitespace;
+        }
+
+        if self.starts_with("<#") {
+            self.advance_until_after("#>");
+            return TokenKind::BlockComment;
+        }
+
+        if character == '#' {
+            self.advance_while(|value| value != '\n' && value != '\r');
+            return TokenKind::Comment;
+        }
+
+        if character == '`' {
+            self.advance_char();
+            if self.starts_with("\r\n") {
+                self.advance_str("\r\n");
+                return TokenKind::LineContinuation;
+            }
+            if self.peek() == Some('\n') {
+                self.advance_char();
+                return TokenKind::LineContinuation;
+            }
+            self.advance_char();
+            return TokenKind::Unknown;
+        }
+
+        if self.starts_with("@'") {
+            self.advance_str("@'");
+            self.advance_here_string_body('\'');
+            return TokenKind::HereStringLiteral;
+        }
+
+        if self.starts_with("@\"") {
+            self.advance_str("@\"");
+            self.modes.push(Mode::ExpandableHereString);
+            return self.scan_expandable_fragment(true);
+        }
+
+        if character == '\'' {
+            self.advance_char();
+            self.advance_single_quoted_body();
+            return TokenKind::StringLiteral;
+        }
+
+        if character == '"' {
+            self.advance_char();
+            self.modes.push(Mode::ExpandableString);
+            return self.scan_expandable_fragment(false);
+        }
+
+        if character == '$' {
+            return self.scan_dollar();
+        }
+
+        if character == '@' {
+            return self.scan_at();
+        }
+
+        // `--%` 之后到行尾（或管道符）的内容原样传给原生程序，整体作为一个 token。
+        if self.starts_with("--%") {
+            self.advance_str("--%");
+            self.advance_while(|value| value != '\n' && value != '\r' && value != '|');
+            return TokenKind::StopParsing;
+        }
+
+        if character == '-'
+            && self
+                .peek_nth(1)
+                .is_some_and(|value| value.is_ascii_alphabetic() || value == '_')
+        {
+            let start = self.cursor;
+            self.advance_char();
+            self.advance_while(is_identifier_char);
+            if operators::is_named_operator(&self.input[start + 1..self.cursor]) {
+                return TokenKind::NamedOperator;
+            }
+            return TokenKind::Parameter;
+        }
+
+        if character.is_ascii_digit() {
+            self.advance_number();
+            return TokenKind::Number;
+        }
+
+        if character.is_alphabetic() || character == '_' {
+            self.advance_while(|value| is_identifier_char(value) || value == '-');
+            return TokenKind::Word;
+        }
+
+        let punctuation = match character {
+            '(' => Some(TokenKind::LParen),
+            ')' => Some(TokenKind::RParen),
+            '{' => Some(TokenKind::LBrace),
+            '}' => Some(TokenKind::RBrace),
+            '[' => Some(TokenKind::LBracket),
+            ']' => Some(TokenKind::RBracket),
+            ';' => Some(TokenKind::Semicolon),
+            ',' => Some(TokenKind::Comma),
+            _ => None,
+        };
+        if let Some(kind) = punctuation {
+            self.advance_char();
+            return kind;
+        }
+
+        if self.starts_with("::") {
+            self.advance_str("::");
+            return TokenKind::DoubleColon;
+        }
+
+        if let Some(operator) = OPERATORS.iter().find(|value| self.starts_with(value)) {
+            self.advance_str(operator);
+            return TokenKind::Operator;
+        }
+
+        self.advance_char();
+        match character {
+            '|' => TokenKind::Pipe,
+            '&' => TokenKind::Ampersand,
+            '.' => TokenKind::Dot,
+            _ => TokenKind::Unknown,
+        }
+    }
+
+    fn scan_dollar(&mut self) -> TokenKind {
+        self.advance_char();
+        match self.peek() {
+            Some('(') => {
+                self.advance_char();
+                TokenKind::SubExpressionStart
+            }
+            Some('{') => {
+                self.advance_until_after("}");
+                TokenKind::Variable
+            }
+            Some('$' | '?' | '^') => {
+                self.advance_char();
+                TokenKind::Variable
+            }
+            Some(value) if is_identifier_char(value) => {
+                self.advance_while(is_identifier_char);
+                if self.peek() == Some(':') && self.peek_nth(1).is_some_and(is_identifier_char) {
+                    self.advance_char();
+                    self.advance_while(is_identifier_char);
+                }
+                TokenKind::Variable
+            }
+            _ => TokenKind::Unknown,
+        }
+    }
+
+    fn scan_at(&mut self) -> TokenKind {
+        self.advance_char();
+        match self.peek() {
+            Some('(') => {
+                self.advance_char();
+                TokenKind::ArrayExpressionStart
+            }
+            Some('{') => {
+                self.advance_char();
+                TokenKind::HashtableStart
+            }
+            Some(value) if is_identifier_char(value) => {
+                self.advance_while(is_identifier_char);
+                TokenKind::SplattedVariable
+            }
+            _ => TokenKind::Unknown,
+        }
+    }
+
+    fn advance_single_quoted_body(&mut self) {
+        while let Some(character) = self.advance_char() {
+            if character == '\'' {
+                if self.peek() == Some('\'') {
+                    self.advance_char();
+                    continue;
+                }
+                return;
+            }
+        }
+    }
+
+    fn mode(&self) -> Mode {
+        self.modes.last().copied().unwrap_or(Mode::Code)
+    }
+
+    // 可展开字符串按片段输出：字面文本保持为字符串 token，`$( )` 内部压栈回到代码模式继续切分。
+    fn scan_expandable_fragment(&mut self, here: bool) -> TokenKind {
+        let kind = if here {
+            TokenKind::ExpandableHereString
+        } else {
+            TokenKind::ExpandableString
+        };
+        let fragment_start = self.cursor;
+
+        while let Some(character) = self.peek() {
+            if self.starts_with("$(") {
+                if self.cursor == fragment_start {
+                    self.advance_str("$(");
+                    self.modes.push(Mode::SubExpression { paren_depth: 0 });
+                    return TokenKind::SubExpressionStart;
+                }
+                return kind;
+            }
+
+            if character == '`' {
+                self.advance_char();
+                self.advance_char();
+                continue;
+            }
+
+            if here {
+                if self.starts_with("\"@") && self.is_line_start() {
+                    self.advance_str("\"@");
+                    self.modes.pop();
+                    return kind;
+                }
+            } else if character == '"' {
+                self.advance_char();
+                if self.peek() == Some('"') {
+                    self.advance_char();
+                    continue;
+                }
+                self.modes.pop();
+                return kind;
+            }
+
+            self.advance_char();
+        }
+
+        kind
+    }
+
+    fn track_sub_expression_depth(&mut self, kind: TokenKind) {
+        let Some(Mode::SubExpression { paren_depth }) = self.modes.last_mut() else {
+            return;
+        };
+
+        match kind {
+            TokenKind::LParen | TokenKind::SubExpressionStart | TokenKind::ArrayExpressionStart => {
+                *paren_depth += 1;
+            }
+            TokenKind::RParen if *paren_depth == 0 => {
+                self.modes.pop();
+            }
+            TokenKind::RParen => *paren_depth -= 1,
+            _ => {}
+        }
+    }
+
+    fn advance_here_string_body(&mut self, quote: char) {
+        while self.cursor < self.input.len() {
+            if self.peek() == Some(quote) && self.peek_nth(1) == Some('@') && self.is_line_start() {
+                self.advance_char();
+                self.advance_char();
+                return;
+            }
+            self.advance_char();
+        }
+    }
+
+    fn advance_number(&mut self) {
+        if self.starts_with("0x") || self.starts_with("0X") {
+            self.advance_str("0x");
+            self.advance_while(|value| value.is_ascii_hexdigit());
+        } else {
+            self.advance_while(|value| value.is_ascii_digit());
+            if self.peek() == Some('.')
+                && self.peek_nth(1).is_some_and(|value| value.is_ascii_digit())
+            {
+                self.advance_char();
+                self.advance_while(|value| value.is_ascii_digit());
+            }
+        }
+        // 数字后缀（kb/mb/gb、类型后缀、指数）直接并入数字 token，避免被误判为命令名。
+        self.advance_while(|value| value.is_ascii_alphanumeric());
+    }
+
+    fn is_line_start(&self) -> bool {
+        self.cursor == 0 || self.input[..self.cursor].ends_with('\n')
+    }
+
+    fn starts_with(&self, pattern: &str) -> bool {
+        self.input[self.cursor..].starts_with(pattern)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.cursor..].chars().next()
+    }
+
+    fn peek_nth(&self, offset: usize) -> Option<char> {
+        self.input[self.cursor..].chars().nth(offset)
+    }
+
+    fn advance_char(&mut self) -> Option<char> {
+        let character = self.peek()?;
+        self.cursor += character.len_utf8();
+        if character == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(character)
+    }
+
+    fn advance_str(&mut self, pattern: &str) {
+        for _ in pattern.chars() {
+            self.advance_char();
+        }
+    }
+
+    fn advance_while(&mut self, predicate: impl Fn(char) -> bool) {
+        while let Some(character) = self.peek() {
+            if !predicate(character) {
+                break;
+            }
+            self.advance_char();
+        }
+    }
+
+    fn advance_until_after(&mut self, terminator: &str) {
+        while self.cursor < self.input.len() {
+            if self.starts_with(terminator) {
+                self.advance_str(terminator);
+                return;
+            }
+            self.advance_char();
+        }
+    }
+}
+
+fn is_identifier_char(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TokenKind, tokenize};
+
+    fn kinds_and_texts(input: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(input)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (token.kind, token.text(input)))
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_command_with_parameters_and_variables() {
+        let tokens = kinds_and_texts("Get-ChildItem -Path $env:TEMP @splat | Measure-Object\n");
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Word, "Get-ChildItem"),
+                (TokenKind::Parameter, "-Path"),
+                (TokenKind::Variable, "$env:TEMP"),
+                (TokenKind::SplattedVariable, "@splat"),
+                (TokenKind::Pipe, "|"),
+                (TokenKind::Word, "Measure-Object"),
+                (TokenKind::NewLine, "\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_named_operators_separately_from_parameters() {
+        let tokens = kinds_and_texts("$a -CEQ 1 -and -not $b -As [int] -Path");
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Variable, "$a"),
+                (TokenKind::NamedOperator, "-CEQ"),
+                (TokenKind::Number, "1"),
+                (TokenKind::NamedOperator, "-and"),
+                (TokenKind::NamedOperator, "-not"),
+                (TokenKind::Variable, "$b"),
+                (TokenKind::NamedOperator, "-As"),
+                (TokenKind::LBracket, "["),
+                (TokenKind::Word, "int"),
+                (TokenKind::RBracket, "]"),
+                (TokenKind::Parameter, "-Path"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_type_literal_member_access_and_numbers() {
+        let tokens = kinds_and_texts("[math]::Round($value.Length, 2) - 1kb");
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::LBracket, "["),
+                (TokenKind::Word, "math"),
+                (TokenKind::RBracket, "]"),
+                (TokenKind::DoubleColon, "::"),
+                (TokenKind::Word, "Round"),
+                (TokenKind::LParen, "("),
+                (TokenKind::Variable, "$value"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Word, "Length"),
+                (TokenKind::Comma, ","),
+                (TokenKind::Number, "2"),
+                (TokenKind::RParen, ")"),
+                (TokenKind::Operator, "-"),
+                (TokenKind::Number, "1kb"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_strings_comments_and_here_strings_as_single_tokens() {
+        let input = "'it''s' \"a `\" b\" # note\n<# block #>@'\nraw\n'@";
+        let tokens = kinds_and_texts(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::StringLiteral, "'it''s'"),
+                (TokenKind::ExpandableString, "\"a `\" b\""),
+                (TokenKind::Comment, "# note"),
+                (TokenKind::NewLine, "\n"),
+                (TokenKind::BlockComment, "<# block #>"),
+                (TokenKind::HereStringLiteral, "@'\nraw\n'@"),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_sub_expressions_out_of_expandable_strings() {
+        let input = "\"Found $(get-item (\"x$(1)\")) `$(no) $name\"";
+        let tokens = kinds_and_texts(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::ExpandableString, "\"Found "),
+                (TokenKind::SubExpressionStart, "$("),
+                (TokenKind::Word, "get-item"),
+                (TokenKind::LParen, "("),
+                (TokenKind::ExpandableString, "\"x"),
+                (TokenKind::SubExpressionStart, "$("),
+                (TokenKind::Number, "1"),
+                (TokenKind::RParen, ")"),
+                (TokenKind::ExpandableString, "\""),
+                (TokenKind::RParen, ")"),
+                (TokenKind::RParen, ")"),
+                (TokenKind::ExpandableString, " `$(no) $name\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn returns_to_string_after_leading_sub_expression() {
+        let tokens = kinds_and_texts("\"$($a)+json\"");
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::SubExpressionStart, "\"$("),
+                (TokenKind::Variable, "$a"),
+                (TokenKind::RParen, ")"),
+                (TokenKind::ExpandableString, "+json\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_sub_expressions_out_of_expandable_here_strings() {
+        let input = "@\"\n$(write-host 'a')\n\"@ | out-null";
+        let tokens = kinds_and_texts(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::ExpandableHereString, "@\"\n"),
+                (TokenKind::SubExpressionStart, "$("),
+                (TokenKind::Word, "write-host"),
+                (TokenKind::StringLiteral, "'a'"),
+                (TokenKind::RParen, ")"),
+                (TokenKind::ExpandableHereString, "\n\"@"),
+                (TokenKind::Pipe, "|"),
+                (TokenKind::Word, "out-null"),
+            ]
+        );
+    }
+
+    #[test]
+    fn stop_parsing_token_runs_to_end_of_line_or_pipe() {
+        let tokens = kinds_and_texts("icacls x --% /grant $a:(F) -r | out-null\n");
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Word, "icacls"),
+                (TokenKind::Word, "x"),
+                (TokenKind::StopParsing, "--% /grant $a:(F) -r "),
+                (TokenKind::Pipe, "|"),
+                (TokenKind::Word, "out-null"),
+                (TokenKind::NewLine, "\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tracks_line_and_column() {
+        let input = "Write-Host `\n  -Object $x\r\n$y";
+        let tokens: Vec<_> = tokenize(input)
+            .into_iter()
+            .filter(|token| !token.kind.is_trivia())
+            .map(|token| (token.kind, token.line, token.column))
+            .collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Word, 1, 1),
+                (TokenKind::Parameter, 2, 3),
+                (TokenKind::Variable, 2, 11),
+                (TokenKind::NewLine, 2, 13),
+                (TokenKind::Variable, 3, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn concatenated_token_text_round_trips_input() {
+        let input =
+            "function Foo {\n  param([string]$Name = \"x\")\n  & { $Name }; $a ??= @(1..3)\n}\n";
+        let rebuilt: String = tokenize(input)
+            .iter()
+            .map(|token| token.text(input))
+            .collect();
+
+        assert_eq!(rebuilt, input);
+    }
+}
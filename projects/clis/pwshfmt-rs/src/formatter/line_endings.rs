@@ -0,0 +1,120 @@
+use crate::config::LineEnding;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineEndingCounts {
+    pub lf: usize,
+    pub crlf: usize,
+}
+
+impl LineEndingCounts {
+    pub fn is_mixed(&self) -> bool {
+        self.lf > 0 && self.crlf > 0
+    }
+
+    // 以出现次数多的换行符为准，持平或没有换行时取 LF。
+    fn dominant(&self) -> &'static str {
+        if self.crlf > self.lf { "\r\n" } else { "\n" }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewlineOutcome {
+    pub formatted: String,
+    pub line_ending_fixes: usize,
+    pub final_newline_fixes: usize,
+}
+
+pub fn count_line_endings(input: &str) -> LineEndingCounts {
+    let crlf = input.matches("\r\n").count();
+    LineEndingCounts {
+        lf: input.matches('\n').count() - crlf,
+        crlf,
+    }
+}
+
+pub fn normalize(input: &str, style: LineEnding, insert_final_newline: bool) -> NewlineOutcome {
+    let counts = count_line_endings(input);
+    let target = match style {
+        LineEnding::Lf => "\n",
+        LineEnding::Crlf => "\r\n",
+        LineEnding::Auto | LineEnding::Preserve => counts.dominant(),
+    };
+
+    let (mut formatted, line_ending_fixes) = match style {
+        LineEnding::Preserve => (input.to_string(), 0),
+        _ if target == "\n" => (input.replace("\r\n", "\n"), counts.crlf),
+        _ => {
+            let mut output = String::with_capacity(input.len() + counts.lf);
+            for (index, line) in input.split('\n').enumerate() {
+                if index > 0 {
+                    output.push_str("\r\n");
+                }
+                output.push_str(line.strip_suffix('\r').unwrap_or(line));
+            }
+            (output, counts.lf)
+        }
+    };
+
+    let mut final_newline_fixes = 0;
+    if insert_final_newline && !formatted.is_empty() && !formatted.ends_with('\n') {
+        formatted.push_str(target);
+        final_newline_fixes = 1;
+    }
+
+    NewlineOutcome {
+        formatted,
+        line_ending_fixes,
+        final_newline_fixes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_line_endings, normalize};
+    use crate::config::LineEnding;
+
+    #[test]
+    fn counts_and_detects_mixed_line_endings() {
+        let counts = count_line_endings("a\r\nb\nc\r\n");
+
+        assert_eq!(counts.lf, 1);
+        assert_eq!(counts.crlf, 2);
+        assert!(counts.is_mixed());
+        assert!(!count_line_endings("a\nb\n").is_mixed());
+    }
+
+    #[test]
+    fn converts_to_requested_line_ending() {
+        let lf = normalize("a\r\nb\nc\r\n", LineEnding::Lf, false);
+        assert_eq!(lf.formatted, "a\nb\nc\n");
+        assert_eq!(lf.line_ending_fixes, 2);
+
+        let crlf = normalize("a\r\nb\nc", LineEnding::Crlf, false);
+        assert_eq!(crlf.formatted, "a\r\nb\r\nc");
+        assert_eq!(crlf.line_ending_fixes, 1);
+    }
+
+    #[test]
+    fn auto_uses_dominant_line_ending_and_preserve_keeps_input() {
+        let auto = normalize("a\r\nb\nc\r\n", LineEnding::Auto, false);
+        assert_eq!(auto.formatted, "a\r\nb\r\nc\r\n");
+        assert_eq!(auto.line_ending_fixes, 1);
+
+        let preserve = normalize("a\r\nb\n", LineEnding::Preserve, false);
+        assert_eq!(preserve.formatted, "a\r\nb\n");
+        assert_eq!(preserve.line_ending_fixes, 0);
+    }
+
+    #[test]
+    fn inserts_final_newline_with_target_line_ending() {
+        let outcome = normalize("a\r\nb", LineEnding::Preserve, true);
+        assert_eq!(outcome.formatted, "a\r\nb\r\n");
+        assert_eq!(outcome.final_newline_fixes, 1);
+
+        assert_eq!(normalize("", LineEnding::Lf, true).formatted, "");
+        assert_eq!(
+            normalize("a\n", LineEnding::Lf, true).final_newline_fixes,
+            0
+        );
+    }
+}
@@ -1,394 +1,946 @@
-#[derive(Debug, Clone)]
+pub mod aliases;
+pub mod dictionary;
+pub mod invocation;
+pub mod keywords;
+pub mod layout;
+pub mod lexer;
+pub mod line_endings;
+pub mod operators;
+pub mod safety;
+pub mod suppression;
+pub mod types;
+pub mod variables;
+pub mod workspace;
+
+use std::path::Path;
+
+use crate::config::{CaseStyle, Config, RuleName, UnsafePolicy};
+use crate::error::Result;
+use crate::summary::FixCounts;
+
+use self::dictionary::CasingDictionary;
+use self::invocation::{ActiveCommand, CommandSource, Invocation, InvocationTracker};
+use self::layout::{LayoutOptions, TokenContext};
+use self::lexer::{Token, TokenKind, tokenize};
+use self::safety::{UnsafeFinding, UnsafeKind, UnsafeRegion, is_dynamic_call_operator};
+use self::suppression::{SuppressedRegion, Suppression};
+
+#[derive(Debug, Clone, Default)]
 pub struct FormatOutcome {
     pub formatted: String,
     pub command_fixes: usize,
+    pub function_fixes: usize,
     pub parameter_fixes: usize,
-    pub unsafe_detected: bool,
+    pub keyword_fixes: usize,
+    pub operator_fixes: usize,
+    pub type_fixes: usize,
+    pub variable_fixes: usize,
+    pub alias_fixes: usize,
+    pub member_fixes: usize,
+    pub layout_fixes: usize,
+    pub unsafe_findings: Vec<UnsafeFinding>,
+    pub unsafe_regions: Vec<UnsafeRegion>,
+    pub suppressed_regions: Vec<SuppressedRegion>,
+}
+
+impl FormatOutcome {
+    pub fn fix_counts(&self) -> FixCounts {
+        FixCounts {
+            command: self.command_fixes,
+            function: self.function_fixes,
+            parameter: self.parameter_fixes,
+            keyword: self.keyword_fixes,
+            operator: self.operator_fixes,
+            type_name: self.type_fixes,
+            variable: self.variable_fixes,
+            alias: self.alias_fixes,
+            member: self.member_fixes,
+            layout: self.layout_fixes,
+            ..FixCounts::default()
+        }
+    }
+
+    // 文件头含 `# pwshfmt: skip` 时整个文件不做任何处理，包括换行符与编码。
+    pub fn file_suppressed(&self) -> bool {
+        self.suppressed_regions
+            .iter()
+            .any(|region| region.reason == Suppression::File)
+    }
 }
 
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    pub dictionary: CasingDictionary,
+    pub workspace_functions: CasingDictionary,
+    pub keyword_case: CaseStyle,
+    pub type_accelerator_case: CaseStyle,
+    pub expand_aliases: bool,
+    pub expand_platform_aliases: bool,
+    pub layout: Option<LayoutOptions>,
+    pub preserve_unsafe: bool,
+    pub rules: RuleSwitches,
+}
+
+// 只有开关的规则；关闭后对应 token 原样输出，调用跟踪不受影响。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ScanState {
-    Code,
-    SingleQuoted,
-    DoubleQuoted,
-    LineComment,
-    BlockComment,
-    HereSingleQuoted,
-    HereDoubleQuoted,
+pub struct RuleSwitches {
+    pub command_case: bool,
+    pub parameter_case: bool,
+    pub operator_case: bool,
+    pub type_case: bool,
+    pub variable_case: bool,
+    pub member_case: bool,
 }
 
-pub fn format_content(input: &str) -> FormatOutcome {
-    let mut output = String::with_capacity(input.len());
-    let mut state = ScanState::Code;
-    let mut index = 0usize;
-
-    let mut command_fixes = 0usize;
-    let mut parameter_fixes = 0usize;
-    let mut unsafe_detected = false;
-
-    while index < input.len() {
-        match state {
-            ScanState::Code => {
-                if starts_with_at(input, index, "<#") {
-                    output.push_str("<#");
-                    index += 2;
-                    state = ScanState::BlockComment;
-                    continue;
-                }
+impl Default for RuleSwitches {
+    fn default() -> Self {
+        Self {
+            command_case: true,
+            parameter_case: true,
+            operator_case: true,
+            type_case: true,
+            variable_case: true,
+            member_case: true,
+        }
+    }
+}
 
-                if starts_with_at(input, index, "@\"") {
-                    output.push_str("@\"");
-                    index += 2;
-                    state = ScanState::HereDoubleQuoted;
-                    continue;
-                }
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            dictionary: CasingDictionary::builtin().clone(),
+            workspace_functions: CasingDictionary::default(),
+            keyword_case: CaseStyle::default(),
+            type_accelerator_case: CaseStyle::default(),
+            expand_aliases: false,
+            expand_platform_aliases: false,
+            layout: None,
+            preserve_unsafe: true,
+            rules: RuleSwitches::default(),
+        }
+    }
+}
 
-                if starts_with_at(input, index, "@'") {
-                    output.push_str("@'");
-                    index += 2;
-                    state = ScanState::HereSingleQuoted;
-                    continue;
+impl FormatOptions {
+    pub fn from_config(config: &Config, cwd: &Path) -> Result<Self> {
+        let dictionary_paths: Vec<_> = config
+            .casing_dictionaries
+            .iter()
+            .map(|path| {
+                if path.is_absolute() {
+                    path.clone()
+                } else {
+                    cwd.join(path)
                 }
+            })
+            .collect();
+        let rules = &config.rules;
 
-                let Some(character) = input[index..].chars().next() else {
-                    break;
-                };
-                let char_len = character.len_utf8();
+        Ok(Self {
+            dictionary: CasingDictionary::load(&dictionary_paths)?,
+            workspace_functions: CasingDictionary::default(),
+            keyword_case: rules.keyword_case.effective_style(),
+            type_accelerator_case: rules.type_case.effective_style(),
+            expand_aliases: rules.alias_expansion.enabled,
+            expand_platform_aliases: rules.alias_expansion.platform,
+            layout: rules.layout.enabled.then_some(LayoutOptions {
+                indent_style: config.indent_style,
+                indent_width: config.indent_width,
+                align_assignments: config.align_assignments,
+            }),
+            preserve_unsafe: config.effective_unsafe_policy() != UnsafePolicy::FormatAnyway,
+            rules: RuleSwitches {
+                command_case: rules.command_case.enabled,
+                parameter_case: rules.parameter_case.enabled,
+                operator_case: rules.operator_case.enabled,
+                type_case: rules.type_case.enabled,
+                variable_case: rules.variable_case.enabled,
+                member_case: rules.member_case.enabled,
+            },
+        })
+    }
+}
 
-                if character == '#' {
-                    output.push('#');
-                    index += char_len;
-                    state = ScanState::LineComment;
-                    continue;
-                }
+pub fn format_content(input: &str) -> FormatOutcome {
+    format_content_with(input, &FormatOptions::default())
+}
 
-                if character == '\'' {
-                    output.push('\'');
-                    index += char_len;
-                    state = ScanState::SingleQuoted;
-                    continue;
-                }
+pub fn format_content_with(input: &str, options: &FormatOptions) -> FormatOutcome {
+    format_content_allowing(input, options, &[])
+}
 
-                if character == '"' {
-                    output.push('"');
-                    index += char_len;
-                    state = ScanState::DoubleQuoted;
-                    continue;
-                }
+// 含动态调用时再格式化一遍，所在语句原样保留，其余部分照常修正；`allowed` 中的种类视为已审查。
+pub fn format_content_allowing(
+    input: &str,
+    options: &FormatOptions,
+    allowed: &[UnsafeKind],
+) -> FormatOutcome {
+    let (mut outcome, mut contexts) = Formatter::new(input, options, allowed, Vec::new()).run();
+    let regions = std::mem::take(&mut outcome.unsafe_regions);
+    if options.preserve_unsafe && !regions.is_empty() {
+        (outcome, contexts) = Formatter::new(input, options, allowed, regions).run();
+    }
+    if let Some(layout_options) = &options.layout
+        && !outcome.file_suppressed()
+    {
+        debug_assert_eq!(
+            tokenize(&outcome.formatted).len(),
+            contexts.len(),
+            "大小写修正不应改变 token 划分"
+        );
+        let (formatted, fixes) = layout::apply(&outcome.formatted, &contexts, layout_options);
+        outcome.formatted = formatted;
+        outcome.layout_fixes = fixes;
+    }
+    outcome
+}
 
-                if character == '&' && is_dynamic_call_operator(input, index) {
-                    unsafe_detected = true;
-                    output.push('&');
-                    index += char_len;
-                    continue;
-                }
+struct Formatter<'a> {
+    input: &'a str,
+    options: &'a FormatOptions,
+    tokens: Vec<Token>,
+    invocations: InvocationTracker,
+    outcome: FormatOutcome,
+    skip_to: usize,
+    attribute_arguments: Option<usize>,
+    unsafe_tokens: Vec<usize>,
+    unsafe_findings: Vec<UnsafeFinding>,
+    allowed_kinds: &'a [UnsafeKind],
+    allowed_lines: Vec<usize>,
+    protected: Vec<UnsafeRegion>,
+    suppressed: Vec<SuppressedRegion>,
+    workflow_bodies: Vec<(usize, usize)>,
+    member_declarations: Vec<usize>,
+    rules: RuleSwitches,
+    keyword_case: CaseStyle,
+}
 
-                if character == '-'
-                    && let Some((token, next_index)) = read_word(input, index + char_len)
-                    && let Some(canonical) = canonical_parameter(token)
-                {
-                    if token != canonical {
-                        parameter_fixes += 1;
-                    }
-                    output.push('-');
-                    output.push_str(canonical);
-                    index = next_index;
-                    continue;
+impl<'a> Formatter<'a> {
+    fn new(
+        input: &'a str,
+        options: &'a FormatOptions,
+        allowed_kinds: &'a [UnsafeKind],
+        protected: Vec<UnsafeRegion>,
+    ) -> Self {
+        let tokens = tokenize(input);
+        Self {
+            input,
+            options,
+            allowed_lines: safety::allowed_lines(&tokens, input),
+            suppressed: suppression::suppressed_regions(&tokens, input),
+            workflow_bodies: keywords::workflow_bodies(&tokens, input),
+            member_declarations: keywords::member_declarations(&tokens, input),
+            tokens,
+            invocations: InvocationTracker::default(),
+            outcome: FormatOutcome {
+                formatted: String::with_capacity(input.len()),
+                ..FormatOutcome::default()
+            },
+            skip_to: 0,
+            attribute_arguments: None,
+            unsafe_tokens: Vec::new(),
+            unsafe_findings: Vec::new(),
+            allowed_kinds,
+            protected,
+            rules: options.rules,
+            keyword_case: options.keyword_case,
+        }
+    }
+
+    // 同时返回每个 token 所处的调用上下文，供排版阶段区分表达式与命令参数。
+    fn run(mut self) -> (FormatOutcome, Vec<TokenContext>) {
+        let mut contexts = Vec::with_capacity(self.tokens.len());
+        for index in 0..self.tokens.len() {
+            let protected = self.protected.iter().any(|region| region.contains(index))
+                || self.is_suppressed(index);
+            contexts.push(match self.invocations.current() {
+                _ if protected || self.suppresses_rule(index, RuleName::Layout) => {
+                    TokenContext::Verbatim
                 }
+                Invocation::Command(_) => TokenContext::CommandArgument,
+                Invocation::Native | Invocation::CallTarget => TokenContext::NativeArgument,
+                _ => TokenContext::Code,
+            });
+            if index < self.skip_to {
+                continue;
+            }
+            self.apply_rule_suppression(index);
+            if protected {
+                self.visit_verbatim(index);
+            } else {
+                self.visit(index);
+            }
+        }
 
-                if character.is_ascii_alphabetic()
-                    && let Some((token, next_index)) = read_command_token(input, index)
-                {
-                    let token_lower = token.to_ascii_lowercase();
-                    if token_lower == "invoke-expression" {
-                        unsafe_detected = true;
-                    }
+        self.outcome.unsafe_findings = std::mem::take(&mut self.unsafe_findings);
+        if self.protected.is_empty() {
+            self.outcome.unsafe_regions =
+                safety::unsafe_regions(&self.tokens, self.input, &self.unsafe_tokens);
+        } else {
+            self.outcome.unsafe_regions = std::mem::take(&mut self.protected);
+        }
+        self.outcome.suppressed_regions = std::mem::take(&mut self.suppressed);
+        (self.outcome, contexts)
+    }
 
-                    if let Some(canonical) = canonical_command(&token_lower) {
-                        if token != canonical {
-                            command_fixes += 1;
-                        }
-                        output.push_str(canonical);
-                    } else {
-                        output.push_str(token);
-                    }
+    fn is_suppressed(&self, index: usize) -> bool {
+        self.suppressed
+            .iter()
+            .any(|region| region.suppresses_all() && region.contains(index))
+    }
 
-                    index = next_index;
-                    continue;
+    fn suppresses_rule(&self, index: usize, rule: RuleName) -> bool {
+        self.suppressed
+            .iter()
+            .any(|region| region.suppresses(rule) && region.contains(index))
+    }
+
+    // `SuppressMessage('PSUseCorrectCasing', '')` 等只关闭部分规则，按 token 所在区域收窄开关。
+    fn apply_rule_suppression(&mut self, index: usize) {
+        let base = self.options.rules;
+        let rules = RuleSwitches {
+            command_case: base.command_case && !self.suppresses_rule(index, RuleName::CommandCase),
+            parameter_case: base.parameter_case
+                && !self.suppresses_rule(index, RuleName::ParameterCase),
+            operator_case: base.operator_case
+                && !self.suppresses_rule(index, RuleName::OperatorCase),
+            type_case: base.type_case && !self.suppresses_rule(index, RuleName::TypeCase),
+            variable_case: base.variable_case
+                && !self.suppresses_rule(index, RuleName::VariableCase),
+            member_case: base.member_case && !self.suppresses_rule(index, RuleName::MemberCase),
+        };
+        self.rules = rules;
+        self.keyword_case = if self.suppresses_rule(index, RuleName::KeywordCase) {
+            CaseStyle::Preserve
+        } else {
+            self.options.keyword_case
+        };
+    }
+
+    // 仍然走一遍常规流程以维持调用状态，但丢弃修正结果、输出原文。
+    fn visit_verbatim(&mut self, index: usize) {
+        let outcome = std::mem::take(&mut self.outcome);
+        self.visit(index);
+        self.outcome = outcome;
+
+        let end = self.skip_to.max(index + 1);
+        let original = &self.input[self.tokens[index].start..self.tokens[end - 1].end];
+        self.push(original);
+    }
+
+    fn visit(&mut self, index: usize) {
+        let token = self.tokens[index];
+        let text = token.text(self.input);
+        if let Some(kind) = self.unsafe_kind(index)
+            && !self.allowed_kinds.contains(&kind)
+            && !self.allowed_lines.contains(&token.line)
+            && !self.is_suppressed(index)
+        {
+            self.unsafe_tokens.push(index);
+            self.unsafe_findings
+                .push(UnsafeFinding::new(kind, &token, self.input));
+        }
+
+        match token.kind {
+            TokenKind::Parameter => self.visit_parameter(text),
+            TokenKind::NamedOperator => self.visit_named_operator(text),
+            TokenKind::LBracket if self.is_type_position(index) => self.visit_type_literal(index),
+            TokenKind::Word | TokenKind::Operator if let Some(command) = self.alias_at(index) => {
+                self.visit_alias(text, command);
+            }
+            TokenKind::Word if self.is_member_name(index) => self.visit_member(index),
+            TokenKind::Word if self.invocations.current() == &Invocation::Attribute => {
+                match types::canonical_attribute_argument(text) {
+                    Some(canonical) => self.push_type_name(text, canonical),
+                    None => self.push(text),
                 }
+            }
+            TokenKind::Word if self.is_assignment_target(index) => self.push(text),
+            TokenKind::Word => self.visit_word(index, text),
+            _ => {
+                self.track_statement(index);
+                match token.kind {
+                    TokenKind::Variable | TokenKind::SplattedVariable => self.push_variable(text),
+                    _ => self.push(text),
+                }
+            }
+        }
+    }
 
-                output.push(character);
-                index += char_len;
+    // 命令位置上的 Invoke-Expression/iex/Add-Type、以变量或表达式为目标的 `&`，以及 `[scriptblock]::Create`。
+    fn unsafe_kind(&self, index: usize) -> Option<UnsafeKind> {
+        let token = self.tokens[index];
+        match token.kind {
+            // 只有命令位置上的 `&` 是调用运算符，语句末尾的 `&` 是后台作业。
+            TokenKind::Ampersand
+                if self.invocations.current() == &Invocation::Idle
+                    && is_dynamic_call_operator(&self.tokens, index) =>
+            {
+                Some(UnsafeKind::DynamicCall)
             }
-            ScanState::SingleQuoted => {
-                let Some(character) = input[index..].chars().next() else {
-                    break;
-                };
-                let char_len = character.len_utf8();
+            TokenKind::Word if self.is_member_name(index) => {
+                let is_create = token.text(self.input).eq_ignore_ascii_case("create")
+                    && self.tokens[index - 1].kind == TokenKind::DoubleColon
+                    && index >= 3
+                    && self.tokens[index - 2].kind == TokenKind::RBracket
+                    && self.tokens[index - 3].kind == TokenKind::Word
+                    && self.tokens[index - 3]
+                        .text(self.input)
+                        .eq_ignore_ascii_case("scriptblock");
+                is_create.then_some(UnsafeKind::ScriptBlockCreate)
+            }
+            TokenKind::Word
+                if matches!(
+                    self.invocations.current(),
+                    Invocation::Idle | Invocation::CallTarget
+                ) && !self.is_assignment_target(index) =>
+            {
+                let text = token.text(self.input);
+                if text.eq_ignore_ascii_case("invoke-expression") {
+                    Some(UnsafeKind::InvokeExpression)
+                } else if text.eq_ignore_ascii_case("iex") {
+                    Some(UnsafeKind::IexAlias)
+                } else if text.eq_ignore_ascii_case("add-type") {
+                    Some(UnsafeKind::AddType)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
 
-                output.push(character);
-                index += char_len;
+    // 只有语句/管道元素开头、`&` 或 `.` 之后的位置才是命令位置，其余裸词都是参数或表达式的一部分。
+    fn track_statement(&mut self, index: usize) {
+        let token = self.tokens[index];
+        let text = token.text(self.input);
+        let current = self.invocations.current().clone();
 
-                if character == '\'' {
-                    if starts_with_at(input, index, "'") {
-                        output.push('\'');
-                        index += 1;
-                    } else {
-                        state = ScanState::Code;
-                    }
+        match token.kind {
+            // `& { ... } -Name x` 的参数交给脚本块的 param 块，按未知 PowerShell 命令处理。
+            TokenKind::LBrace if current == Invocation::CallTarget => {
+                self.invocations.begin_command("", CommandSource::Unknown);
+                self.invocations.enter_group();
+            }
+            TokenKind::StringLiteral | TokenKind::ExpandableString
+                if current == Invocation::CallTarget && is_script_path(text) =>
+            {
+                self.invocations.begin_command(text, CommandSource::Unknown);
+            }
+            TokenKind::LParen
+            | TokenKind::SubExpressionStart
+            | TokenKind::ArrayExpressionStart
+            | TokenKind::LBrace
+            | TokenKind::HashtableStart
+            | TokenKind::LBracket => {
+                if current == Invocation::CallTarget {
+                    self.invocations.set(Invocation::Native);
                 }
+                self.invocations.enter_group();
+                if self.attribute_arguments == Some(index) {
+                    self.attribute_arguments = None;
+                    self.invocations.set(Invocation::Attribute);
+                }
+                // 类型字面量与特性中的名称不是命令或关键字：`[enum]::GetValues(...)`。
+                if token.kind == TokenKind::LBracket {
+                    self.invocations.set(Invocation::Expression);
+                }
+            }
+            TokenKind::RParen | TokenKind::RBrace | TokenKind::RBracket => {
+                self.invocations.leave_group();
             }
-            ScanState::DoubleQuoted => {
-                let Some(character) = input[index..].chars().next() else {
-                    break;
+            TokenKind::NewLine | TokenKind::Semicolon | TokenKind::Pipe => {
+                self.invocations.end_statement();
+            }
+            TokenKind::Operator if text == "&&" || text == "||" => self.invocations.end_statement(),
+            // 赋值号右侧重新回到命令位置：`$x = git ...` 中的 git 才是被调用的命令。
+            // 命令参数中的 `pid=`、`key=value` 只是参数文本，不是赋值。
+            TokenKind::Operator
+                if is_assignment_operator(text)
+                    && !matches!(current, Invocation::Command(_) | Invocation::Native) =>
+            {
+                self.invocations.end_statement();
+            }
+            TokenKind::Ampersand if current == Invocation::Idle => {
+                self.invocations.set(Invocation::CallTarget);
+            }
+            TokenKind::Dot if current == Invocation::Idle => {
+                let next = self.tokens.get(index + 1).map(|next| next.kind);
+                let next_text = self.tokens.get(index + 1).map(|next| next.text(self.input));
+                let invocation = if next == Some(TokenKind::Whitespace) {
+                    Invocation::CallTarget
+                } else if matches!(next_text, Some("/" | "\\")) {
+                    Invocation::Native
+                } else {
+                    Invocation::Expression
                 };
-                let char_len = character.len_utf8();
+                self.invocations.set(invocation);
+            }
+            TokenKind::Variable
+            | TokenKind::SplattedVariable
+            | TokenKind::Number
+            | TokenKind::StringLiteral
+            | TokenKind::HereStringLiteral
+            | TokenKind::ExpandableString
+            | TokenKind::ExpandableHereString
+            | TokenKind::DoubleColon => match current {
+                Invocation::Idle => self.invocations.set(Invocation::Expression),
+                Invocation::CallTarget => self.invocations.set(Invocation::Native),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    // 命令参数位置上的 `[int]` 是普通字符串参数；紧跟变量/成员/括号之后的 `[` 是索引。
+    fn is_type_position(&self, index: usize) -> bool {
+        if matches!(
+            self.invocations.current(),
+            Invocation::Command(_) | Invocation::Native
+        ) {
+            return false;
+        }
 
-                output.push(character);
-                index += char_len;
+        index == 0
+            || !matches!(
+                self.tokens[index - 1].kind,
+                TokenKind::Variable
+                    | TokenKind::SplattedVariable
+                    | TokenKind::Word
+                    | TokenKind::Number
+                    | TokenKind::RParen
+                    | TokenKind::RBrace
+                    | TokenKind::StringLiteral
+                    | TokenKind::HereStringLiteral
+                    | TokenKind::ExpandableString
+                    | TokenKind::ExpandableHereString
+            )
+    }
+
+    // 类型字面量整体输出；特性只处理到名称为止，参数列表交回常规流程。
+    fn visit_type_literal(&mut self, open: usize) {
+        let mut pieces: Vec<(usize, usize, Option<String>)> = Vec::new();
+        let mut depth = 0usize;
+        let mut cursor = open;
 
-                if character == '`' {
-                    if let Some(escaped) = input[index..].chars().next() {
-                        output.push(escaped);
-                        index += escaped.len_utf8();
+        let end = loop {
+            let Some(token) = self.tokens.get(cursor) else {
+                break None;
+            };
+            match token.kind {
+                TokenKind::LBracket => depth += 1,
+                TokenKind::RBracket => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break Some(cursor + 1);
+                    }
+                }
+                TokenKind::Comma | TokenKind::Whitespace => {}
+                TokenKind::Word => {
+                    let name_end = self.dotted_name_end(cursor);
+                    let name = &self.input[token.start..self.tokens[name_end - 1].end];
+                    let next = self.tokens.get(name_end).map(|next| next.kind);
+                    if depth == 1 && next == Some(TokenKind::LParen) {
+                        pieces.push((cursor, name_end, types::canonical_attribute(name)));
+                        self.attribute_arguments = Some(name_end);
+                        break Some(name_end);
                     }
+                    let canonical = types::canonical_type(name, self.options.type_accelerator_case);
+                    pieces.push((cursor, name_end, canonical));
+                    cursor = name_end;
                     continue;
                 }
+                _ => break None,
+            }
+            cursor += 1;
+        };
 
-                if character == '"' {
-                    state = ScanState::Code;
-                }
+        let Some(end) = end else {
+            self.attribute_arguments = None;
+            self.track_statement(open);
+            self.push("[");
+            return;
+        };
+
+        let is_attribute = self.attribute_arguments.is_some();
+        if is_attribute {
+            self.track_statement(open);
+        }
+
+        let mut position = open;
+        for (start, name_end, canonical) in pieces {
+            for token in &self.tokens[position..start] {
+                self.outcome.formatted.push_str(token.text(self.input));
             }
-            ScanState::LineComment => {
-                let Some(character) = input[index..].chars().next() else {
-                    break;
-                };
-                let char_len = character.len_utf8();
-                output.push(character);
-                index += char_len;
+            let original = &self.input[self.tokens[start].start..self.tokens[name_end - 1].end];
+            match canonical {
+                Some(canonical) => self.push_type_name(original, &canonical),
+                None => self.push(original),
+            }
+            position = name_end;
+        }
+        for token in &self.tokens[position..end] {
+            self.outcome.formatted.push_str(token.text(self.input));
+        }
+        self.skip_to = end;
+    }
 
-                if character == '\n' {
-                    state = ScanState::Code;
+    fn dotted_name_end(&self, start: usize) -> usize {
+        let mut end = start + 1;
+        while self
+            .tokens
+            .get(end)
+            .is_some_and(|token| token.kind == TokenKind::Dot)
+            && self
+                .tokens
+                .get(end + 1)
+                .is_some_and(|token| token.kind == TokenKind::Word)
+        {
+            end += 2;
+        }
+        end
+    }
+
+    fn push_variable(&mut self, text: &str) {
+        if !self.rules.variable_case {
+            self.push(text);
+            return;
+        }
+        match variables::canonical_variable(text) {
+            Some(canonical) => {
+                if canonical != text {
+                    self.outcome.variable_fixes += 1;
                 }
+                self.push(&canonical);
             }
-            ScanState::BlockComment => {
-                if starts_with_at(input, index, "#>") {
-                    output.push_str("#>");
-                    index += 2;
-                    state = ScanState::Code;
-                    continue;
+            None => self.push(text),
+        }
+    }
+
+    fn push_type_name(&mut self, text: &str, canonical: &str) {
+        if !self.rules.type_case {
+            self.push(text);
+            return;
+        }
+        if text != canonical {
+            self.outcome.type_fixes += 1;
+        }
+        self.push(canonical);
+    }
+
+    fn visit_parameter(&mut self, text: &str) {
+        if self.invocations.current() == &Invocation::Native || !self.rules.parameter_case {
+            self.push(text);
+            return;
+        }
+
+        match resolve_parameter(self.options, self.invocations.current_command(), &text[1..]) {
+            Some(canonical) => {
+                if text[1..] != *canonical {
+                    self.outcome.parameter_fixes += 1;
                 }
+                self.push("-");
+                self.push(canonical);
+            }
+            None => self.push(text),
+        }
+    }
 
-                let Some(character) = input[index..].chars().next() else {
-                    break;
-                };
-                output.push(character);
-                index += character.len_utf8();
+    // 命令调用中同名参数优先（如某命令自带 `-Not`），原生程序参数保持原样，其余按运算符统一为小写。
+    fn visit_named_operator(&mut self, text: &str) {
+        match self.invocations.current() {
+            Invocation::Native => {
+                self.push(text);
+                return;
             }
-            ScanState::HereSingleQuoted => {
-                if starts_with_at(input, index, "'@") && is_line_start(input, index) {
-                    output.push_str("'@");
-                    index += 2;
-                    state = ScanState::Code;
-                    continue;
+            Invocation::Command(command) => {
+                let specific = resolve_command_parameter(self.options, command, &text[1..]);
+                if let Some(canonical) = specific {
+                    if !self.rules.parameter_case {
+                        self.push(text);
+                        return;
+                    }
+                    if text[1..] != *canonical {
+                        self.outcome.parameter_fixes += 1;
+                    }
+                    self.push("-");
+                    self.push(canonical);
+                    return;
                 }
+            }
+            Invocation::Idle => self.invocations.set(Invocation::Expression),
+            _ => {}
+        }
 
-                let Some(character) = input[index..].chars().next() else {
-                    break;
-                };
-                output.push(character);
-                index += character.len_utf8();
+        if !self.rules.operator_case {
+            self.push(text);
+            return;
+        }
+        let lowered = text.to_ascii_lowercase();
+        if lowered != text {
+            self.outcome.operator_fixes += 1;
+        }
+        self.push(&lowered);
+    }
+
+    fn visit_word(&mut self, index: usize, text: &str) {
+        match self.invocations.current() {
+            Invocation::Idle | Invocation::CallTarget => self.visit_command_name(index, text),
+            // `foreach ($item in $items)` 中的 in 出现在表达式之后，其后回到命令位置。
+            Invocation::Expression if text.eq_ignore_ascii_case("in") => {
+                self.push_keyword(text, "In");
+                self.invocations.set(Invocation::Idle);
             }
-            ScanState::HereDoubleQuoted => {
-                if starts_with_at(input, index, "\"@") && is_line_start(input, index) {
-                    output.push_str("\"@");
-                    index += 2;
-                    state = ScanState::Code;
-                    continue;
-                }
+            Invocation::Declaration
+            | Invocation::Command(_)
+            | Invocation::Native
+            | Invocation::Expression
+            | Invocation::Attribute => self.push(text),
+        }
+    }
 
-                let Some(character) = input[index..].chars().next() else {
-                    break;
-                };
-                output.push(character);
-                index += character.len_utf8();
+    fn visit_command_name(&mut self, index: usize, text: &str) {
+        // `[void] Process() { }` 中的方法名与枚举值是标识符，不是关键字或命令。
+        if self.is_member_declaration(index) {
+            self.push(text);
+            self.invocations.set(Invocation::Declaration);
+            return;
+        }
+        let in_workflow = self
+            .workflow_bodies
+            .iter()
+            .any(|(open, close)| (*open..*close).contains(&index));
+        let keyword = keywords::keyword(text).or_else(|| {
+            in_workflow
+                .then(|| keywords::workflow_keyword(text))
+                .flatten()
+        });
+        if let Some(canonical) = keyword {
+            if keywords::is_declaration_keyword(text) {
+                self.invocations.set(Invocation::Declaration);
+            }
+            self.push_keyword(text, canonical);
+            return;
+        }
+
+        match self.resolve_command(text) {
+            Some((canonical, source)) => {
+                self.push_command(text, canonical, source);
+                self.invocations.begin_command(canonical, source);
+            }
+            None if invocation::looks_like_powershell_command(text) => {
+                self.push(text);
+                self.invocations.begin_command(text, CommandSource::Unknown);
+            }
+            // 裸名与路径（`tar`、`./build`、`tool.exe`）按原生程序处理，参数保持原样。
+            None => {
+                self.push(text);
+                self.invocations.set(Invocation::Native);
             }
         }
     }
 
-    FormatOutcome {
-        formatted: output,
-        command_fixes,
-        parameter_fixes,
-        unsafe_detected,
+    // `foreach` 只有出现在管道符之后时才是 ForEach-Object 的别名，语句开头是关键字。
+    fn alias_at(&self, index: usize) -> Option<&'static str> {
+        if !self.options.expand_aliases
+            || !matches!(
+                self.invocations.current(),
+                Invocation::Idle | Invocation::CallTarget
+            )
+            || self.is_member_name(index)
+            || self.is_member_declaration(index)
+            || self.is_assignment_target(index)
+        {
+            return None;
+        }
+
+        let text = self.tokens[index].text(self.input);
+        if self.tokens[index].kind == TokenKind::Operator && text != "%" && text != "?" {
+            return None;
+        }
+        if text.eq_ignore_ascii_case("foreach") {
+            let previous = self.tokens[..index]
+                .iter()
+                .rev()
+                .find(|token| !token.kind.is_trivia());
+            if previous.map(|token| token.kind) != Some(TokenKind::Pipe) {
+                return None;
+            }
+        }
+
+        aliases::resolve_alias(text, self.options.expand_platform_aliases)
     }
-}
 
-fn starts_with_at(input: &str, index: usize, pattern: &str) -> bool {
-    input.get(index..).is_some_and(|value| value.starts_with(pattern))
-}
+    fn visit_alias(&mut self, text: &str, command: &'static str) {
+        let (canonical, source) = self
+            .resolve_command(command)
+            .unwrap_or((command, CommandSource::Unknown));
+        if text != canonical {
+            self.outcome.alias_fixes += 1;
+        }
+        self.push(canonical);
+        self.invocations.begin_command(canonical, source);
+    }
 
-fn read_word(input: &str, start: usize) -> Option<(&str, usize)> {
-    if start >= input.len() {
-        return None;
+    fn resolve_command(&self, text: &str) -> Option<(&'a str, CommandSource)> {
+        let options = self.options;
+        options
+            .dictionary
+            .command(text)
+            .map(|canonical| (canonical, CommandSource::Dictionary))
+            .or_else(|| {
+                options
+                    .workspace_functions
+                    .command(text)
+                    .map(|canonical| (canonical, CommandSource::Workspace))
+            })
     }
 
-    let mut end = start;
-    let mut has_character = false;
-    for (offset, character) in input[start..].char_indices() {
-        if character.is_ascii_alphanumeric() {
-            has_character = true;
-            end = start + offset + character.len_utf8();
-            continue;
+    fn push_command(&mut self, text: &str, canonical: &str, source: CommandSource) {
+        if !self.rules.command_case {
+            self.push(text);
+            return;
         }
-        break;
+        if text != canonical {
+            match source {
+                CommandSource::Workspace => self.outcome.function_fixes += 1,
+                CommandSource::Dictionary | CommandSource::Unknown => {
+                    self.outcome.command_fixes += 1;
+                }
+            }
+        }
+        self.push(canonical);
     }
 
-    if !has_character {
-        return None;
+    // 只修正表达式之后的成员访问：`$s.tolower()`、`[string]::isnullorempty`；
+    // 命令参数中的 `file.length` 是裸字符串，不做修改。
+    fn visit_member(&mut self, index: usize) {
+        let text = self.tokens[index].text(self.input);
+        let canonical = (self.rules.member_case && self.is_member_access(index))
+            .then(|| self.options.dictionary.member(text))
+            .flatten();
+
+        match canonical {
+            Some(canonical) => {
+                if canonical != text {
+                    self.outcome.member_fixes += 1;
+                }
+                self.push(canonical);
+            }
+            None => self.push(text),
+        }
     }
 
-    Some((&input[start..end], end))
-}
+    fn is_member_access(&self, index: usize) -> bool {
+        if index < 2 {
+            return false;
+        }
 
-fn read_command_token(input: &str, start: usize) -> Option<(&str, usize)> {
-    if start >= input.len() {
-        return None;
+        let target = index - 2;
+        match self.tokens[target].kind {
+            TokenKind::Variable
+            | TokenKind::RParen
+            | TokenKind::RBracket
+            | TokenKind::StringLiteral => true,
+            TokenKind::Word => self.is_member_name(target) && self.is_member_access(target),
+            _ => false,
+        }
     }
 
-    let mut end = start;
-    for (offset, character) in input[start..].char_indices() {
-        if character.is_ascii_alphanumeric() || character == '-' {
-            end = start + offset + character.len_utf8();
-            continue;
+    fn push_keyword(&mut self, text: &str, canonical: &str) {
+        let styled = match self.keyword_case {
+            CaseStyle::Lower => canonical.to_ascii_lowercase(),
+            CaseStyle::Upper => canonical.to_ascii_uppercase(),
+            CaseStyle::Pascal => canonical.to_string(),
+            CaseStyle::Preserve => text.to_string(),
+        };
+        if styled != text {
+            self.outcome.keyword_fixes += 1;
         }
-        break;
+        self.push(&styled);
     }
 
-    if end == start {
-        return None;
+    fn is_member_name(&self, index: usize) -> bool {
+        index > 0
+            && matches!(
+                self.tokens[index - 1].kind,
+                TokenKind::Dot | TokenKind::DoubleColon
+            )
     }
 
-    Some((&input[start..end], end))
-}
+    // 类中的 `[void] ls() { }` 声明的是方法名，不是命令。
+    fn is_member_declaration(&self, index: usize) -> bool {
+        self.member_declarations.binary_search(&index).is_ok()
+    }
 
-fn is_line_start(input: &str, index: usize) -> bool {
-    if index == 0 {
-        return true;
+    // 哈希表键与 `name = value` 形式的左值不是命令：`@{ write-host = 1 }`。
+    fn is_assignment_target(&self, index: usize) -> bool {
+        self.tokens[index + 1..]
+            .iter()
+            .find(|token| token.kind != TokenKind::Whitespace)
+            .is_some_and(|token| {
+                token.kind == TokenKind::Operator && is_assignment_operator(token.text(self.input))
+            })
     }
 
-    input[..index]
-        .chars()
-        .next_back()
-        .is_some_and(|value| value == '\n')
+    fn push(&mut self, text: &str) {
+        self.outcome.formatted.push_str(text);
+    }
 }
 
-fn is_dynamic_call_operator(input: &str, index: usize) -> bool {
-    let mut cursor = index + '&'.len_utf8();
-
-    while let Some(character) = input.get(cursor..).and_then(|value| value.chars().next()) {
-        if character.is_whitespace() {
-            cursor += character.len_utf8();
-            continue;
-        }
-
-        return matches!(character, '$' | '(' | '{' | '\'' | '"');
-    }
+// 通过字符串路径调用的 PowerShell 脚本，其参数与普通命令一样修正；其余路径视为原生程序。
+fn is_script_path(text: &str) -> bool {
+    let path = text.trim_matches(|quote| quote == '\'' || quote == '"');
+    [".ps1", ".psm1"].iter().any(|extension| {
+        path.len() > extension.len()
+            && path[path.len() - extension.len()..].eq_ignore_ascii_case(extension)
+    })
+}
 
-    false
+fn is_assignment_operator(text: &str) -> bool {
+    matches!(text, "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "??=")
 }
 
-fn canonical_command(token_lower: &str) -> Option<&'static str> {
-    match token_lower {
-        "add-content" => Some("Add-Content"),
-        "compare-object" => Some("Compare-Object"),
-        "convertfrom-json" => Some("ConvertFrom-Json"),
-        "convertto-json" => Some("ConvertTo-Json"),
-        "export-csv" => Some("Export-Csv"),
-        "foreach-object" => Some("ForEach-Object"),
-        "get-childitem" => Some("Get-ChildItem"),
-        "get-command" => Some("Get-Command"),
-        "get-content" => Some("Get-Content"),
-        "get-date" => Some("Get-Date"),
-        "get-item" => Some("Get-Item"),
-        "get-location" => Some("Get-Location"),
-        "get-process" => Some("Get-Process"),
-        "import-csv" => Some("Import-Csv"),
-        "import-module" => Some("Import-Module"),
-        "install-module" => Some("Install-Module"),
-        "invoke-command" => Some("Invoke-Command"),
-        "invoke-expression" => Some("Invoke-Expression"),
-        "invoke-formatter" => Some("Invoke-Formatter"),
-        "join-path" => Some("Join-Path"),
-        "measure-object" => Some("Measure-Object"),
-        "new-item" => Some("New-Item"),
-        "out-file" => Some("Out-File"),
-        "remove-item" => Some("Remove-Item"),
-        "resolve-path" => Some("Resolve-Path"),
-        "select-object" => Some("Select-Object"),
-        "set-content" => Some("Set-Content"),
-        "set-item" => Some("Set-Item"),
-        "set-location" => Some("Set-Location"),
-        "set-strictmode" => Some("Set-StrictMode"),
-        "sort-object" => Some("Sort-Object"),
-        "split-path" => Some("Split-Path"),
-        "start-process" => Some("Start-Process"),
-        "stop-process" => Some("Stop-Process"),
-        "test-path" => Some("Test-Path"),
-        "where-object" => Some("Where-Object"),
-        "write-debug" => Some("Write-Debug"),
-        "write-error" => Some("Write-Error"),
-        "write-host" => Some("Write-Host"),
-        "write-output" => Some("Write-Output"),
-        "write-verbose" => Some("Write-Verbose"),
-        "write-warning" => Some("Write-Warning"),
-        _ => None,
-    }
+// 命令已知时优先查该命令自己的参数集合，未命中再回退全局参数表。
+fn resolve_parameter<'a>(
+    options: &'a FormatOptions,
+    command: Option<&ActiveCommand>,
+    name: &str,
+) -> Option<&'a str> {
+    command
+        .and_then(|command| resolve_command_parameter(options, command, name))
+        .or_else(|| options.dictionary.parameter(name))
 }
 
-fn canonical_parameter(token: &str) -> Option<&'static str> {
-    match token.to_ascii_lowercase().as_str() {
-        "all" => Some("All"),
-        "argumentlist" => Some("ArgumentList"),
-        "as" => Some("As"),
-        "command" => Some("Command"),
-        "confirm" => Some("Confirm"),
-        "depth" => Some("Depth"),
-        "debug" => Some("Debug"),
-        "erroraction" => Some("ErrorAction"),
-        "exclude" => Some("Exclude"),
-        "file" => Some("File"),
-        "filter" => Some("Filter"),
-        "force" => Some("Force"),
-        "gitchanged" => Some("GitChanged"),
-        "help" => Some("Help"),
-        "include" => Some("Include"),
-        "inputobject" => Some("InputObject"),
-        "literalpath" => Some("LiteralPath"),
-        "modulename" => Some("ModuleName"),
-        "name" => Some("Name"),
-        "noprofile" => Some("NoProfile"),
-        "outputpath" => Some("OutputPath"),
-        "path" => Some("Path"),
-        "pipelinevariable" => Some("PipelineVariable"),
-        "recurse" => Some("Recurse"),
-        "scope" => Some("Scope"),
-        "scriptblock" => Some("ScriptBlock"),
-        "settings" => Some("Settings"),
-        "showonly" => Some("ShowOnly"),
-        "strict" => Some("Strict"),
-        "value" => Some("Value"),
-        "verbose" => Some("Verbose"),
-        "warningaction" => Some("WarningAction"),
-        "whatif" => Some("WhatIf"),
-        "write" => Some("Write"),
-        _ => None,
+fn resolve_command_parameter<'a>(
+    options: &'a FormatOptions,
+    command: &ActiveCommand,
+    name: &str,
+) -> Option<&'a str> {
+    match command.source {
+        CommandSource::Dictionary => options.dictionary.command_parameter(&command.name, name),
+        CommandSource::Workspace => options
+            .workspace_functions
+            .command_parameter(&command.name, name),
+        CommandSource::Unknown => None,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::format_content;
+    use super::dictionary::DictionaryFile;
+    use super::{
+        CaseStyle, FormatOptions, FormatOutcome, LayoutOptions, RuleSwitches, UnsafeKind,
+        format_content, format_content_allowing, format_content_with,
+    };
+
+    fn format_with_layout(input: &str) -> FormatOutcome {
+        let options = FormatOptions {
+            layout: Some(LayoutOptions::default()),
+            ..FormatOptions::default()
+        };
+        format_content_with(input, &options)
+    }
 
     #[test]
     fn fixes_command_and_parameter_casing() {
@@ -398,7 +950,7 @@ mod tests {
         assert_eq!(output.formatted, "Get-ChildItem -Path .\n");
         assert_eq!(output.command_fixes, 1);
         assert_eq!(output.parameter_fixes, 1);
-        assert!(!output.unsafe_detected);
+        assert!(output.unsafe_findings.is_empty());
     }
 
     #[test]
@@ -416,7 +968,207 @@ mod tests {
         let input = "& $scriptBlock\n";
         let output = format_content(input);
 
-        assert!(output.unsafe_detected);
+        assert_eq!(output.unsafe_findings.len(), 1);
+        assert_eq!(output.unsafe_findings[0].kind, UnsafeKind::DynamicCall);
+    }
+
+    #[test]
+    fn reports_each_unsafe_construct_with_position() {
+        let input = "if ($a) {\n    iex $code\n}\n$sb = [ScriptBlock]::Create($text)\nadd-type -typedefinition $source\nInvoke-Expression $command\nGet-Command Invoke-Expression\n";
+        let findings: Vec<_> = format_content(input)
+            .unsafe_findings
+            .into_iter()
+            .map(|finding| (finding.kind, finding.line, finding.column, finding.snippet))
+            .collect();
+
+        assert_eq!(
+            findings,
+            vec![
+                (UnsafeKind::IexAlias, 2, 5, "iex $code".to_string()),
+                (
+                    UnsafeKind::ScriptBlockCreate,
+                    4,
+                    22,
+                    "$sb = [ScriptBlock]::Create($text)".to_string()
+                ),
+                (
+                    UnsafeKind::AddType,
+                    5,
+                    1,
+                    "add-type -typedefinition $source".to_string()
+                ),
+                (
+                    UnsafeKind::InvokeExpression,
+                    6,
+                    1,
+                    "Invoke-Expression $command".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn allow_dynamic_comments_and_kinds_skip_reviewed_constructs() {
+        let input = "iex $a # pwshfmt: allow-dynamic\n# pwshfmt: allow-dynamic\n& $b\n& $c\nadd-type -path x.cs\nget-childitem -path .\n";
+        let output =
+            format_content_allowing(input, &FormatOptions::default(), &[UnsafeKind::AddType]);
+
+        let lines: Vec<_> = output
+            .unsafe_findings
+            .iter()
+            .map(|finding| finding.line)
+            .collect();
+        assert_eq!(lines, vec![4]);
+        assert!(
+            output
+                .formatted
+                .ends_with("& $c\nadd-type -Path x.cs\nGet-ChildItem -Path .\n")
+        );
+    }
+
+    #[test]
+    fn literal_call_targets_are_not_dynamic() {
+        let input = "& 'C:\\tools\\tool.exe' -flag get-item\n& \"$PSScriptRoot/build.ps1\" -verbose\n& { get-childitem -path . } -verbose\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "& 'C:\\tools\\tool.exe' -flag get-item\n& \"$PSScriptRoot/build.ps1\" -Verbose\n& { Get-ChildItem -Path . } -Verbose\n"
+        );
+        assert!(output.unsafe_findings.is_empty());
+        assert_eq!(
+            format_content("& (Join-Path $root 'x.ps1')\n").unsafe_findings[0].kind,
+            UnsafeKind::DynamicCall
+        );
+    }
+
+    #[test]
+    fn trailing_background_operator_is_not_a_dynamic_call() {
+        let input = "Start-Job { sleep 1 } &\n$x = get-item .\nget-item . &\n& $sb\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "Start-Job { sleep 1 } &\n$x = Get-Item .\nGet-Item . &\n& $sb\n"
+        );
+        assert_eq!(output.unsafe_findings.len(), 1);
+        assert_eq!(output.unsafe_findings[0].line, 4);
+    }
+
+    #[test]
+    fn formats_around_statements_with_dynamic_calls() {
+        let input =
+            "get-childitem -path .\n$out = & $sb -path . |\n    out-null\nwrite-host 'done'\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "Get-ChildItem -Path .\n$out = & $sb -path . |\n    out-null\nWrite-Host 'done'\n"
+        );
+        assert_eq!(output.command_fixes, 2);
+        assert_eq!(output.parameter_fixes, 1);
+        assert_eq!(output.unsafe_regions.len(), 1);
+        assert_eq!(
+            (
+                output.unsafe_regions[0].start_line,
+                output.unsafe_regions[0].end_line
+            ),
+            (2, 3)
+        );
+    }
+
+    #[test]
+    fn layout_spaces_braces_after_commands_but_not_inside_barewords() {
+        let input = "get-childitem | foreach-object{ $_ }\nwrite-output a{b}\nget-childitem | foreach-object -Process{ $_ }\n";
+        let output = format_with_layout(input);
+
+        assert_eq!(
+            output.formatted,
+            "Get-ChildItem | ForEach-Object { $_ }\nWrite-Output a{b}\nGet-ChildItem | ForEach-Object -Process { $_ }\n"
+        );
+    }
+
+    #[test]
+    fn layout_spaces_unspaced_hashtable_keys() {
+        let input = "$h = @{\nname=get-item .\nlonger=2\n}\n";
+        let output = format_with_layout(input);
+
+        assert_eq!(
+            output.formatted,
+            "$h = @{\n    name   = Get-Item .\n    longer = 2\n}\n"
+        );
+    }
+
+    #[test]
+    fn layout_leaves_dynamic_statements_untouched() {
+        let input = "if ($a)\n{\nget-item .\n  invoke-expression   $cmd|out-null\n}\n";
+        let output = format_with_layout(input);
+
+        assert_eq!(
+            output.formatted,
+            "if ($a) {\n    Get-Item .\n    invoke-expression   $cmd|out-null\n}\n"
+        );
+    }
+
+    #[test]
+    fn suppression_comments_keep_regions_untouched() {
+        let input = "if ($a) {\n# pwshfmt: off\n  $short   = get-item .\n  $longer  = iex $x\n# pwshfmt: on\n  get-item .\n# pwshfmt-ignore-next-line\n   get-item   .\n}\n";
+        let output = format_with_layout(input);
+
+        assert_eq!(
+            output.formatted,
+            "if ($a) {\n# pwshfmt: off\n  $short   = get-item .\n  $longer  = iex $x\n# pwshfmt: on\n    Get-Item .\n    # pwshfmt-ignore-next-line\n   get-item   .\n}\n"
+        );
+        assert!(output.unsafe_findings.is_empty());
+        assert_eq!(output.suppressed_regions.len(), 2);
+
+        let indented = "if ($a) {\n  # pwshfmt: off\n  $x   = 1\n  # pwshfmt: on\n}\n";
+        assert_eq!(format_with_layout(indented).formatted, indented);
+    }
+
+    #[test]
+    fn script_analyzer_suppressions_only_disable_matching_rules() {
+        let input = "function A {\n[SuppressMessage('PSUseCorrectCasing', '')]\nparam()\n$x=get-item .\n}\nfunction B {\n[SuppressMessage('PSUseConsistentWhitespace', '')]\nparam()\n$x=get-item .\n}\n";
+        let output = format_with_layout(input);
+
+        assert_eq!(
+            output.formatted,
+            "function A {\n    [SuppressMessage('PSUseCorrectCasing', '')]\n    param()\n    $x = get-item .\n}\nfunction B {\n[SuppressMessage('PSUseConsistentWhitespace', '')]\nparam()\n$x=Get-Item .\n}\n"
+        );
+        assert_eq!(output.suppressed_regions.len(), 2);
+    }
+
+    #[test]
+    fn skip_header_leaves_the_whole_file_alone() {
+        let input = "# pwshfmt: skip\nif ($a)\n{\nget-item .\n}\n";
+        let output = format_with_layout(input);
+
+        assert_eq!(output.formatted, input);
+        assert!(output.file_suppressed());
+    }
+
+    #[test]
+    fn fixes_casing_inside_string_sub_expressions() {
+        let input = "\"Found $(get-childitem -path . | measure-object) get-childitem\"\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "\"Found $(Get-ChildItem -Path . | Measure-Object) get-childitem\"\n"
+        );
+        assert_eq!(output.command_fixes, 2);
+        assert_eq!(output.parameter_fixes, 1);
+    }
+
+    #[test]
+    fn fixes_casing_inside_here_string_sub_expressions() {
+        let input = "@\"\nget-item $(join-path -path $root 'x')\n\"@\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "@\"\nget-item $(Join-Path -Path $root 'x')\n\"@\n"
+        );
     }
 
     #[test]
@@ -426,4 +1178,343 @@ mod tests {
 
         assert_eq!(output.formatted, input);
     }
+
+    #[test]
+    fn applies_dictionary_entries_merged_from_options() {
+        let mut options = FormatOptions::default();
+        options.dictionary.merge(&DictionaryFile {
+            commands: vec!["Invoke-Pester".to_string()],
+            parameters: vec!["CI".to_string()],
+            ..DictionaryFile::default()
+        });
+
+        let output = format_content_with("invoke-pester -ci\n", &options);
+
+        assert_eq!(output.formatted, "Invoke-Pester -CI\n");
+        assert_eq!(output.command_fixes, 1);
+        assert_eq!(output.parameter_fixes, 1);
+    }
+
+    #[test]
+    fn counts_workspace_function_fixes_separately() {
+        let mut options = FormatOptions::default();
+        options
+            .workspace_functions
+            .merge(&super::workspace::learn_functions(
+                "function Get-RepoRoot { param($StartPath) }\n",
+            ));
+
+        let output = format_content_with("get-reporoot -startpath .\n", &options);
+
+        assert_eq!(output.formatted, "Get-RepoRoot -StartPath .\n");
+        assert_eq!(output.command_fixes, 0);
+        assert_eq!(output.function_fixes, 1);
+        assert_eq!(output.parameter_fixes, 1);
+    }
+
+    #[test]
+    fn resolves_parameters_against_the_current_command() {
+        let mut options = FormatOptions::default();
+        options.dictionary.merge(&DictionaryFile {
+            command_parameters: [(
+                "Get-Widget".to_string(),
+                vec!["PATH".to_string(), "AsHashtable".to_string()],
+            )]
+            .into(),
+            ..DictionaryFile::default()
+        });
+
+        let input = "convertfrom-json -ashashtable\nget-widget -path (join-path -path a -childpath b) -ashashtable\nget-childitem -ashashtable -path .\n";
+        let output = format_content_with(input, &options);
+
+        assert_eq!(
+            output.formatted,
+            "ConvertFrom-Json -AsHashtable\nGet-Widget -PATH (Join-Path -Path a -ChildPath b) -AsHashtable\nGet-ChildItem -ashashtable -Path .\n"
+        );
+    }
+
+    #[test]
+    fn leaves_native_executable_arguments_alone() {
+        let input = "pwsh -noprofile -file x.ps1\n$out = tar -force -path (join-path -path a b)\nssh -name host; get-item -path .\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "pwsh -noprofile -file x.ps1\n$out = tar -force -path (Join-Path -Path a b)\nssh -name host; Get-Item -Path .\n"
+        );
+    }
+
+    #[test]
+    fn keeps_text_after_stop_parsing_token() {
+        let input = "get-childitem --% -path write-host\nget-childitem -path .\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "Get-ChildItem --% -path write-host\nGet-ChildItem -Path .\n"
+        );
+    }
+
+    #[test]
+    fn workflow_keywords_are_only_recognized_inside_workflows() {
+        let input = "PARALLEL -j 4 gzip\nworkflow Test-Flow {\n    PARALLEL {\n        Sequence { InlineScript { get-item . } }\n    }\n}\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "PARALLEL -j 4 gzip\nworkflow Test-Flow {\n    parallel {\n        sequence { inlinescript { Get-Item . } }\n    }\n}\n"
+        );
+        assert_eq!(output.keyword_fixes, 3);
+    }
+
+    #[test]
+    fn foreach_in_is_followed_by_command_position() {
+        let output =
+            format_content("foreach ($f in get-childitem -force) {}\nforeach ($f in $items) {}\n");
+
+        assert_eq!(
+            output.formatted,
+            "foreach ($f in Get-ChildItem -Force) {}\nforeach ($f in $items) {}\n"
+        );
+    }
+
+    #[test]
+    fn unknown_verb_noun_commands_fall_back_to_global_parameters() {
+        let output = format_content("copy-item -path a\nget-member -name x\nmove-item -path a\n");
+
+        assert_eq!(
+            output.formatted,
+            "copy-item -Path a\nget-member -Name x\nmove-item -Path a\n"
+        );
+        assert_eq!(output.parameter_fixes, 3);
+    }
+
+    #[test]
+    fn native_commands_keep_their_arguments() {
+        let input = "tar -force\nssh -verbose\n./build -path a\ntool.exe -path a\nstart-stop-daemon -verbose\n";
+        let output = format_content(input);
+
+        assert_eq!(output.formatted, input);
+        assert_eq!(output.parameter_fixes, 0);
+    }
+
+    #[test]
+    fn only_fixes_commands_in_command_position() {
+        let input = "remove-item write-host.txt dest\n$x = @{ write-host = 1; path = get-item }\n$obj.get-item; & get-childitem; . get-date\nget-item (get-date) | sort-object\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "Remove-Item write-host.txt dest\n$x = @{ write-host = 1; path = Get-Item }\n$obj.get-item; & Get-ChildItem; . Get-Date\nGet-Item (Get-Date) | Sort-Object\n"
+        );
+        assert_eq!(output.command_fixes, 7);
+    }
+
+    #[test]
+    fn equals_signs_in_arguments_do_not_return_to_command_position() {
+        let options = FormatOptions {
+            expand_aliases: true,
+            expand_platform_aliases: true,
+            ..FormatOptions::default()
+        };
+        let input = "ps -axo pid=,%mem=\ngit config alias.x=get-item\nwrite-output a=get-item\n";
+        let output = format_content_with(input, &options);
+
+        assert_eq!(
+            output.formatted,
+            "Get-Process -axo pid=,%mem=\ngit config alias.x=get-item\nWrite-Output a=get-item\n"
+        );
+        assert_eq!(output.alias_fixes, 1);
+    }
+
+    #[test]
+    fn expression_statements_do_not_start_commands() {
+        let input = "$items -contains write-host\n[string]$name = join-path a b\nforeach ($item in get-childitem) { write-output $item }\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "$items -contains write-host\n[string]$name = Join-Path a b\nforeach ($item in Get-ChildItem) { Write-Output $item }\n"
+        );
+    }
+
+    #[test]
+    fn lowercases_keywords_by_default() {
+        let input = "Function Get-Foo {\n    Param($Path)\n    ForEach ($item IN $Path) { IF ($item) { Return $item } ElseIf (1) {} }\n}\n[Enum]::GetValues($t); @{ End = 1 }; $x.Process\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "function Get-Foo {\n    param($Path)\n    foreach ($item in $Path) { if ($item) { return $item } elseif (1) {} }\n}\n[Enum]::GetValues($t); @{ End = 1 }; $x.Process\n"
+        );
+        assert_eq!(output.keyword_fixes, 7);
+    }
+
+    #[test]
+    fn applies_configured_keyword_case() {
+        let input = "try { foreach ($x in $y) {} } CATCH {}\n";
+        let mut options = FormatOptions {
+            keyword_case: CaseStyle::Pascal,
+            ..FormatOptions::default()
+        };
+
+        let output = format_content_with(input, &options);
+        assert_eq!(output.formatted, "Try { ForEach ($x In $y) {} } Catch {}\n");
+
+        options.keyword_case = CaseStyle::Preserve;
+        let output = format_content_with(input, &options);
+        assert_eq!(output.formatted, input);
+        assert_eq!(output.keyword_fixes, 0);
+    }
+
+    #[test]
+    fn disabled_rules_leave_their_tokens_untouched() {
+        let input =
+            "get-childitem -path $HOME | where-object { $_.name -EQ [STRING]$x.tostring() }\n";
+        let mut options = FormatOptions::default();
+        options.rules.command_case = false;
+        options.rules.operator_case = false;
+        options.rules.type_case = false;
+
+        let output = format_content_with(input, &options);
+        assert_eq!(
+            output.formatted,
+            "get-childitem -Path $HOME | where-object { $_.name -EQ [STRING]$x.ToString() }\n"
+        );
+
+        options.rules = RuleSwitches {
+            parameter_case: false,
+            variable_case: false,
+            member_case: false,
+            ..RuleSwitches::default()
+        };
+        let output = format_content_with(input, &options);
+        assert_eq!(
+            output.formatted,
+            "Get-ChildItem -path $HOME | Where-Object { $_.name -eq [string]$x.tostring() }\n"
+        );
+    }
+
+    #[test]
+    fn lowercases_named_operators() {
+        let input = "if ($a -EQ 1 -AND -Not ($b -CLike 'x')) { $c = $d -As [int]; $e -Join ',' }\nget-childitem -path . | where-object Name -Match 'x'\nfind . -NOT -name x\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "if ($a -eq 1 -and -not ($b -clike 'x')) { $c = $d -as [int]; $e -join ',' }\nGet-ChildItem -Path . | Where-Object Name -match 'x'\nfind . -NOT -name x\n"
+        );
+        assert_eq!(output.operator_fixes, 7);
+        assert_eq!(output.parameter_fixes, 1);
+    }
+
+    #[test]
+    fn fixes_type_literals_and_attributes() {
+        let input = "[cmdletbinding()]\nparam(\n    [parameter(mandatory, position = 0)][STRING[]]$Path,\n    [validateset('a', 'b')]\n    [system.io.fileinfo]$File\n)\n$x = [io.path]::GetFileName($Path) -as [Int]\n[diagnostics.codeanalysis.suppressmessageattribute('PSAvoidUsingWriteHost', '')]\n$list = [system.collections.generic.list[pscustomobject]]::new()\n$y = $items[0]; $z = [My.Custom]::Value\nwrite-host [String]\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "[CmdletBinding()]\nparam(\n    [Parameter(Mandatory, Position = 0)][string[]]$Path,\n    [ValidateSet('a', 'b')]\n    [System.IO.FileInfo]$File\n)\n$x = [IO.Path]::GetFileName($Path) -as [int]\n[Diagnostics.CodeAnalysis.SuppressMessageAttribute('PSAvoidUsingWriteHost', '')]\n$list = [System.Collections.Generic.List[pscustomobject]]::new()\n$y = $items[0]; $z = [My.Custom]::Value\nWrite-Host [String]\n"
+        );
+        assert_eq!(output.type_fixes, 11);
+    }
+
+    #[test]
+    fn applies_configured_type_accelerator_case() {
+        let options = FormatOptions {
+            type_accelerator_case: CaseStyle::Pascal,
+            ..FormatOptions::default()
+        };
+
+        let output = format_content_with("[math]::Round([string]$a) + [hashtable]@{}\n", &options);
+
+        assert_eq!(
+            output.formatted,
+            "[Math]::Round([String]$a) + [Hashtable]@{}\n"
+        );
+    }
+
+    #[test]
+    fn fixes_automatic_variables_and_scope_qualifiers() {
+        let input = "if ($X -eq $NULL) { $Script:count = $True; $Env:Path; . \"$psscriptroot/x.ps1\" }\nget-childitem @psboundparameters $psscriptroot ${Env:Temp}\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "if ($X -eq $null) { $script:count = $true; $env:Path; . \"$psscriptroot/x.ps1\" }\nGet-ChildItem @PSBoundParameters $PSScriptRoot ${Env:Temp}\n"
+        );
+        assert_eq!(output.variable_fixes, 6);
+    }
+
+    #[test]
+    fn expands_aliases_in_command_position_when_enabled() {
+        let input = "gci -path . | % { $_ } | ? Name | foreach { $_ }\nforeach ($x in $y) { ls -la; $a % 2; $b ? 1 : 2 }\nWrite-Output gci\n";
+        let mut options = FormatOptions::default();
+
+        assert_eq!(format_content_with(input, &options).formatted, input);
+
+        options.expand_aliases = true;
+        let output = format_content_with(input, &options);
+        assert_eq!(
+            output.formatted,
+            "Get-ChildItem -Path . | ForEach-Object { $_ } | Where-Object Name | ForEach-Object { $_ }\nforeach ($x in $y) { ls -la; $a % 2; $b ? 1 : 2 }\nWrite-Output gci\n"
+        );
+        assert_eq!(output.alias_fixes, 4);
+
+        options.expand_platform_aliases = true;
+        let output = format_content_with("ls -Force\n", &options);
+        assert_eq!(output.formatted, "Get-ChildItem -Force\n");
+    }
+
+    #[test]
+    fn keeps_class_member_names_that_match_keywords() {
+        let input = "class Job {\n    [void] Process() { if ($true) { get-item . } }\n    hidden [void] Begin() { }\n    static [int] END([int] $x) { return $x }\n    Filter() { }\n}\nenum Stage { Begin; Process; End }\n";
+
+        assert_eq!(
+            format_content(input).formatted,
+            "class Job {\n    [void] Process() { if ($true) { Get-Item . } }\n    hidden [void] Begin() { }\n    static [int] END([int] $x) { return $x }\n    Filter() { }\n}\nenum Stage { Begin; Process; End }\n"
+        );
+    }
+
+    #[test]
+    fn does_not_expand_aliases_used_as_class_member_names() {
+        let options = FormatOptions {
+            expand_aliases: true,
+            expand_platform_aliases: true,
+            ..FormatOptions::default()
+        };
+        let input = "class ls : Base
+{
+    [void] ls() { ls }
+    static [int] gci([int] $x) { return $x }
+    ls() {}
+}
+enum Shell { ls; gci }
+";
+
+        assert_eq!(
+            format_content_with(input, &options).formatted,
+            "class ls : Base
+{
+    [void] ls() { Get-ChildItem }
+    static [int] gci([int] $x) { return $x }
+    ls() {}
+}
+enum Shell { ls; gci }
+"
+        );
+    }
+
+    #[test]
+    fn fixes_known_member_casing_after_expressions() {
+        let input = "$s.tolower().trim(); $list.add($x); [string]::isnullorempty($v); $path.length\n$obj.customProp.LENGTH; $x.unknownMethod(); remove-item file.length; 'a'.toupper()\n";
+        let output = format_content(input);
+
+        assert_eq!(
+            output.formatted,
+            "$s.ToLower().Trim(); $list.Add($x); [string]::IsNullOrEmpty($v); $path.Length\n$obj.customProp.Length; $x.unknownMethod(); Remove-Item file.length; 'a'.ToUpper()\n"
+        );
+        assert_eq!(output.member_fixes, 7);
+    }
 }
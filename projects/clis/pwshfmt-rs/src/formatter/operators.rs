@@ -0,0 +1,51 @@
+// 支持 `c`/`i` 前缀（大小写敏感/不敏感）变体的比较类运算符。
+const COMPARISON_OPERATORS: [&str; 16] = [
+    "eq",
+    "ne",
+    "gt",
+    "ge",
+    "lt",
+    "le",
+    "like",
+    "notlike",
+    "match",
+    "notmatch",
+    "contains",
+    "notcontains",
+    "in",
+    "notin",
+    "replace",
+    "split",
+];
+
+const OTHER_OPERATORS: [&str; 15] = [
+    "join", "is", "isnot", "as", "and", "or", "xor", "not", "band", "bor", "bxor", "bnot", "shl",
+    "shr", "f",
+];
+
+// 参数名与 `-eq`/`-and` 等运算符在词法上相同，只按名称判断是否属于运算符。
+pub fn is_named_operator(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    if OTHER_OPERATORS.contains(&name.as_str()) || COMPARISON_OPERATORS.contains(&name.as_str()) {
+        return true;
+    }
+
+    name.strip_prefix(['c', 'i'])
+        .is_some_and(|base| COMPARISON_OPERATORS.contains(&base))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_named_operator;
+
+    #[test]
+    fn recognizes_operators_and_case_sensitivity_variants() {
+        assert!(is_named_operator("EQ"));
+        assert!(is_named_operator("cLike"));
+        assert!(is_named_operator("inotmatch"));
+        assert!(is_named_operator("As"));
+        assert!(!is_named_operator("cjoin"));
+        assert!(!is_named_operator("Path"));
+        assert!(!is_named_operator("iff"));
+    }
+}
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+
+use super::lexer::{Token, TokenKind};
+
+const SNIPPET_WIDTH: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsafeKind {
+    DynamicCall,
+    InvokeExpression,
+    IexAlias,
+    ScriptBlockCreate,
+    AddType,
+}
+
+impl UnsafeKind {
+    pub const ALL: [UnsafeKind; 5] = [
+        UnsafeKind::DynamicCall,
+        UnsafeKind::InvokeExpression,
+        UnsafeKind::IexAlias,
+        UnsafeKind::ScriptBlockCreate,
+        UnsafeKind::AddType,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UnsafeKind::DynamicCall => "dynamic_call",
+            UnsafeKind::InvokeExpression => "invoke_expression",
+            UnsafeKind::IexAlias => "iex_alias",
+            UnsafeKind::ScriptBlockCreate => "scriptblock_create",
+            UnsafeKind::AddType => "add_type",
+        }
+    }
+}
+
+// 行号与列号从 1 开始；片段取所在行去掉缩进后的内容。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsafeFinding {
+    pub kind: UnsafeKind,
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+}
+
+impl UnsafeFinding {
+    pub(crate) fn new(kind: UnsafeKind, token: &Token, input: &str) -> Self {
+        let line_start = input[..token.start]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let line_end = input[token.start..]
+            .find('\n')
+            .map_or(input.len(), |index| token.start + index);
+        let line = input[line_start..line_end].trim();
+        let snippet = if line.chars().count() > SNIPPET_WIDTH {
+            format!("{}…", line.chars().take(SNIPPET_WIDTH).collect::<String>())
+        } else {
+            line.to_string()
+        };
+
+        Self {
+            kind,
+            line: token.line,
+            column: token.column,
+            snippet,
+        }
+    }
+}
+
+// `# pwshfmt: allow-dynamic` 写在行尾时作用于本行，独占一行时作用于下一行。
+pub(crate) fn allowed_lines(tokens: &[Token], input: &str) -> Vec<usize> {
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| {
+            token.kind == TokenKind::Comment
+                && directive(token.text(input))
+                    .is_some_and(|directive| directive.eq_ignore_ascii_case("allow-dynamic"))
+        })
+        .map(|(index, token)| {
+            let own_line = tokens[..index]
+                .iter()
+                .rev()
+                .find(|previous| previous.kind != TokenKind::Whitespace)
+                .is_none_or(|previous| previous.kind == TokenKind::NewLine);
+            if own_line { token.line + 1 } else { token.line }
+        })
+        .collect()
+}
+
+// `# pwshfmt: <指令>` 形式的注释，返回去掉首尾空白的指令名。
+pub(crate) fn directive(comment: &str) -> Option<&str> {
+    let (tool, directive) = comment.strip_prefix('#')?.split_once(':')?;
+    tool.trim()
+        .eq_ignore_ascii_case("pwshfmt")
+        .then(|| directive.trim())
+        .filter(|directive| !directive.is_empty())
+}
+
+// 动态调用所在的语句原样保留，行号从 1 开始。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsafeRegion {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub(crate) first_token: usize,
+    pub(crate) last_token: usize,
+}
+
+impl UnsafeRegion {
+    pub(crate) fn contains(&self, index: usize) -> bool {
+        (self.first_token..=self.last_token).contains(&index)
+    }
+}
+
+// 同一语句或同一行内以分号分隔的多处动态调用合并为一个区域。
+pub fn unsafe_regions(tokens: &[Token], input: &str, unsafe_tokens: &[usize]) -> Vec<UnsafeRegion> {
+    let mut spans: Vec<(usize, usize)> = unsafe_tokens
+        .iter()
+        .map(|index| statement_span(tokens, *index))
+        .collect();
+    spans.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (first, last) in spans {
+        match merged.last_mut() {
+            Some(previous)
+                if first <= previous.1
+                    || tokens[previous.1 + 1..first].iter().all(|token| {
+                        matches!(token.kind, TokenKind::Whitespace | TokenKind::Semicolon)
+                    }) =>
+            {
+                previous.1 = previous.1.max(last);
+            }
+            _ => merged.push((first, last)),
+        }
+    }
+
+    merged
+        .into_iter()
+        .map(|(first, last)| {
+            let last_token = tokens[last];
+            UnsafeRegion {
+                start_line: tokens[first].line,
+                end_line: last_token.line + last_token.text(input).matches('\n').count(),
+                first_token: first,
+                last_token: last,
+            }
+        })
+        .collect()
+}
+
+// 向两侧扫描到同层的换行或分号，或外层括号、脚本块的边界；管道与运算符续行属于同一语句。
+fn statement_span(tokens: &[Token], index: usize) -> (usize, usize) {
+    let mut first = index;
+    let mut depth = 0usize;
+    while first > 0 {
+        let kind = tokens[first - 1].kind;
+        if is_closer(kind) {
+            depth += 1;
+        } else if is_opener(kind) {
+            if depth == 0 {
+                break;
+            }
+            depth -= 1;
+        } else if depth == 0 && ends_statement(tokens, first - 1) {
+            break;
+        }
+        first -= 1;
+    }
+
+    let mut last = index;
+    depth = 0;
+    while last + 1 < tokens.len() {
+        let kind = tokens[last + 1].kind;
+        if is_opener(kind) {
+            depth += 1;
+        } else if is_closer(kind) {
+            if depth == 0 {
+                break;
+            }
+            depth -= 1;
+        } else if depth == 0 && ends_statement(tokens, last + 1) {
+            break;
+        }
+        last += 1;
+    }
+
+    while first < index && tokens[first].kind == TokenKind::Whitespace {
+        first += 1;
+    }
+    while last > index && tokens[last].kind == TokenKind::Whitespace {
+        last -= 1;
+    }
+    (first, last)
+}
+
+fn ends_statement(tokens: &[Token], index: usize) -> bool {
+    match tokens[index].kind {
+        TokenKind::Semicolon => true,
+        TokenKind::NewLine => {
+            let continued_before = tokens[..index]
+                .iter()
+                .rev()
+                .find(|token| token.kind != TokenKind::Whitespace)
+                .is_some_and(|token| {
+                    matches!(
+                        token.kind,
+                        TokenKind::Pipe
+                            | TokenKind::Operator
+                            | TokenKind::NamedOperator
+                            | TokenKind::Comma
+                    )
+                });
+            let continued_after = tokens[index + 1..]
+                .iter()
+                .find(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::NewLine))
+                .is_some_and(|token| token.kind == TokenKind::Pipe);
+            !continued_before && !continued_after
+        }
+        _ => false,
+    }
+}
+
+fn is_opener(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::LParen
+            | TokenKind::SubExpressionStart
+            | TokenKind::ArrayExpressionStart
+            | TokenKind::LBrace
+            | TokenKind::HashtableStart
+            | TokenKind::LBracket
+    )
+}
+
+fn is_closer(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::RParen | TokenKind::RBrace | TokenKind::RBracket
+    )
+}
+
+// `& 'tool.exe'`、`& "$PSScriptRoot/x.ps1"`、`& { ... }` 的调用目标在源码中可见；只有变量与表达式才是动态的。
+// 调用目标与 `&` 同属一条语句：行尾的后台作业 `&` 之后是下一条语句。
+pub(crate) fn is_dynamic_call_operator(tokens: &[Token], index: usize) -> bool {
+    tokens[index + 1..]
+        .iter()
+        .find(|token| {
+            !matches!(
+                token.kind,
+                TokenKind::Whitespace | TokenKind::LineContinuation
+            )
+        })
+        .is_some_and(|token| {
+            matches!(
+                token.kind,
+                TokenKind::Variable | TokenKind::SubExpressionStart | TokenKind::LParen
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_dynamic_call_operator, unsafe_regions};
+    use crate::formatter::lexer::{TokenKind, tokenize};
+
+    fn regions(input: &str) -> Vec<(usize, usize)> {
+        let tokens = tokenize(input);
+        let unsafe_tokens: Vec<usize> = tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, token)| token.kind == TokenKind::Ampersand)
+            .map(|(index, _)| index)
+            .collect();
+        unsafe_regions(&tokens, input, &unsafe_tokens)
+            .into_iter()
+            .map(|region| (region.start_line, region.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn call_target_must_follow_on_the_same_statement() {
+        let dynamic = |input: &str| {
+            let tokens = tokenize(input);
+            let index = tokens
+                .iter()
+                .position(|token| token.kind == TokenKind::Ampersand)
+                .expect("ampersand");
+            is_dynamic_call_operator(&tokens, index)
+        };
+
+        assert!(dynamic("& $sb"));
+        assert!(dynamic("& `\n    $sb"));
+        assert!(!dynamic("Start-Job { sleep 1 } &\n$x = 1"));
+        assert!(!dynamic("Start-Job { sleep 1 } &; $x = 1"));
+        assert!(!dynamic("& 'tool.exe' $x"));
+    }
+
+    #[test]
+    fn region_covers_the_enclosing_statement_only() {
+        let input = "get-item .\n$x = & $sb |\n    out-null\nget-item .\n";
+        assert_eq!(regions(input), vec![(2, 3)]);
+    }
+
+    #[test]
+    fn region_stops_at_script_block_and_semicolon() {
+        let input = "if ($a) {\n    get-item .; & $sb\n}\n";
+        assert_eq!(regions(input), vec![(2, 2)]);
+    }
+
+    #[test]
+    fn dynamic_calls_on_the_same_line_merge_into_one_region() {
+        let input = "& $a; & $b\n\n& $c\n";
+        assert_eq!(regions(input), vec![(1, 1), (3, 3)]);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::config::RuleName;
+
+use super::lexer::{Token, TokenKind};
+use super::safety::directive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suppression {
+    Off,
+    IgnoreNextLine,
+    File,
+    SuppressMessage,
+}
+
+impl Suppression {
+    pub fn name(self) -> &'static str {
+        match self {
+            Suppression::Off => "off",
+            Suppression::IgnoreNextLine => "ignore-next-line",
+            Suppression::File => "skip",
+            Suppression::SuppressMessage => "SuppressMessage",
+        }
+    }
+}
+
+// 行号从 1 开始；`rules` 为空时区域内的 token 原样输出，否则只关闭列出的规则。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuppressedRegion {
+    pub reason: Suppression,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub rules: &'static [RuleName],
+    pub(crate) first_token: usize,
+    pub(crate) last_token: usize,
+}
+
+impl SuppressedRegion {
+    fn new(tokens: &[Token], input: &str, reason: Suppression, first: usize, last: usize) -> Self {
+        let first_token = tokens[first];
+        let last_token = tokens[last];
+        // 以换行开头的区域连同下一行的缩进一起保留，行号从下一行算起。
+        let start_line = if first_token.kind == TokenKind::NewLine {
+            first_token.line + 1
+        } else {
+            first_token.line
+        };
+        let end_line = if last_token.kind == TokenKind::NewLine {
+            last_token.line
+        } else {
+            last_token.line + last_token.text(input).matches('\n').count()
+        };
+        Self {
+            reason,
+            start_line,
+            end_line,
+            rules: &[],
+            first_token: first,
+            last_token: last,
+        }
+    }
+
+    pub(crate) fn contains(&self, index: usize) -> bool {
+        (self.first_token..=self.last_token).contains(&index)
+    }
+
+    pub fn suppresses_all(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn suppresses(&self, rule: RuleName) -> bool {
+        self.suppresses_all() || self.rules.contains(&rule)
+    }
+}
+
+const CASING_RULES: [RuleName; 7] = [
+    RuleName::CommandCase,
+    RuleName::ParameterCase,
+    RuleName::KeywordCase,
+    RuleName::OperatorCase,
+    RuleName::TypeCase,
+    RuleName::VariableCase,
+    RuleName::MemberCase,
+];
+
+// PSScriptAnalyzer 格式化规则对应的 pwshfmt 规则；以 `pwshfmt` 开头的规则名关闭全部规则。
+fn rules_for(name: &str) -> Option<&'static [RuleName]> {
+    const LAYOUT_RULES: [&str; 4] = [
+        "PSPlaceOpenBrace",
+        "PSUseConsistentIndentation",
+        "PSUseConsistentWhitespace",
+        "PSAlignAssignmentStatement",
+    ];
+    if name
+        .get(..7)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("pwshfmt"))
+    {
+        Some(&[])
+    } else if name.eq_ignore_ascii_case("PSUseCorrectCasing") {
+        Some(&CASING_RULES)
+    } else if LAYOUT_RULES
+        .iter()
+        .any(|rule| rule.eq_ignore_ascii_case(name))
+    {
+        Some(&[RuleName::Layout])
+    } else {
+        None
+    }
+}
+
+pub(crate) fn suppressed_regions(tokens: &[Token], input: &str) -> Vec<SuppressedRegion> {
+    if tokens.is_empty() {
+        return Vec::new();
+    }
+    let last = tokens.len() - 1;
+    if skips_file(tokens, input) {
+        return vec![SuppressedRegion::new(
+            tokens,
+            input,
+            Suppression::File,
+            0,
+            last,
+        )];
+    }
+
+    let mut regions = Vec::new();
+    let mut off_at = None;
+    for (index, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Comment => {
+                let text = token.text(input);
+                match directive(text).map(str::to_ascii_lowercase).as_deref() {
+                    Some("off") if off_at.is_none() => off_at = Some(line_start(tokens, index)),
+                    Some("on") => {
+                        if let Some(first) = off_at.take() {
+                            regions.push(SuppressedRegion::new(
+                                tokens,
+                                input,
+                                Suppression::Off,
+                                first,
+                                index,
+                            ));
+                        }
+                    }
+                    _ if off_at.is_none() && is_ignore_next_line(text) => {
+                        if let Some(region) = next_line_region(tokens, input, index) {
+                            regions.push(region);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            TokenKind::LBracket if off_at.is_none() => {
+                if let Some(rules) = suppressed_rules(tokens, input, index) {
+                    let (first, last) = enclosing_block(tokens, index);
+                    regions.push(SuppressedRegion {
+                        rules,
+                        ..SuppressedRegion::new(
+                            tokens,
+                            input,
+                            Suppression::SuppressMessage,
+                            first,
+                            last,
+                        )
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    // 没有对应 `on` 的 `off` 一直生效到文件末尾。
+    if let Some(first) = off_at {
+        regions.push(SuppressedRegion::new(
+            tokens,
+            input,
+            Suppression::Off,
+            first,
+            last,
+        ));
+    }
+    regions
+}
+
+// 独占一行的 `off` 标记从行首换行算起，与区域末尾的 `on` 标记一样保留原有缩进。
+fn line_start(tokens: &[Token], comment: usize) -> usize {
+    let previous = tokens[..comment]
+        .iter()
+        .rposition(|token| token.kind != TokenKind::Whitespace);
+    match previous {
+        Some(newline) if tokens[newline].kind == TokenKind::NewLine => newline,
+        _ => comment,
+    }
+}
+
+// `# pwshfmt: skip` 只在代码开始前的注释头中生效。
+fn skips_file(tokens: &[Token], input: &str) -> bool {
+    tokens
+        .iter()
+        .take_while(|token| {
+            matches!(
+                token.kind,
+                TokenKind::Comment | TokenKind::Whitespace | TokenKind::NewLine
+            )
+        })
+        .any(|token| {
+            token.kind == TokenKind::Comment
+                && directive(token.text(input))
+                    .is_some_and(|directive| directive.eq_ignore_ascii_case("skip"))
+        })
+}
+
+fn is_ignore_next_line(comment: &str) -> bool {
+    comment
+        .strip_prefix('#')
+        .is_some_and(|rest| rest.trim().eq_ignore_ascii_case("pwshfmt-ignore-next-line"))
+}
+
+// 从注释行末的换行开始，到下一行的换行之前结束。
+fn next_line_region(tokens: &[Token], input: &str, comment: usize) -> Option<SuppressedRegion> {
+    let first = comment
+        + 1
+        + tokens[comment + 1..]
+            .iter()
+            .position(|token| token.kind == TokenKind::NewLine)?;
+    let last = tokens[first + 1..]
+        .iter()
+        .position(|token| token.kind == TokenKind::NewLine)
+        .map_or(tokens.len() - 1, |offset| first + offset);
+    (last > first)
+        .then(|| SuppressedRegion::new(tokens, input, Suppression::IgnoreNextLine, first, last))
+}
+
+// `[Diagnostics.CodeAnalysis.SuppressMessageAttribute('pwshfmt', '')]`，返回被抑制的规则。
+fn suppressed_rules(tokens: &[Token], input: &str, open: usize) -> Option<&'static [RuleName]> {
+    let mut index = open + 1;
+    let mut name = None;
+    while let Some(token) = tokens.get(index) {
+        match token.kind {
+            TokenKind::Word => name = Some(token.text(input)),
+            TokenKind::Dot => {}
+            _ => break,
+        }
+        index += 1;
+    }
+    let name = name?;
+    if !name.eq_ignore_ascii_case("SuppressMessageAttribute")
+        && !name.eq_ignore_ascii_case("SuppressMessage")
+    {
+        return None;
+    }
+    if tokens.get(index).map(|token| token.kind) != Some(TokenKind::LParen) {
+        return None;
+    }
+
+    let rule = tokens[index + 1..]
+        .iter()
+        .find(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::NewLine))
+        .filter(|token| {
+            matches!(
+                token.kind,
+                TokenKind::StringLiteral | TokenKind::ExpandableString
+            )
+        })?;
+    rules_for(rule.text(input).trim_matches(['\'', '"']))
+}
+
+// 特性作用于所在函数或脚本块；位于顶层时作用于整个文件。
+fn enclosing_block(tokens: &[Token], index: usize) -> (usize, usize) {
+    let mut depth = 0usize;
+    let mut open = None;
+    for candidate in (0..index).rev() {
+        match tokens[candidate].kind {
+            TokenKind::RBrace => depth += 1,
+            TokenKind::LBrace if depth == 0 => {
+                open = Some(candidate);
+                break;
+            }
+            TokenKind::LBrace | TokenKind::HashtableStart => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    let Some(open) = open else {
+        return (0, tokens.len() - 1);
+    };
+
+    depth = 0;
+    for (candidate, token) in tokens.iter().enumerate().skip(open + 1) {
+        match token.kind {
+            TokenKind::LBrace | TokenKind::HashtableStart => depth += 1,
+            TokenKind::RBrace if depth == 0 => return (open + 1, candidate - 1),
+            TokenKind::RBrace => depth -= 1,
+            _ => {}
+        }
+    }
+    (open + 1, tokens.len() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CASING_RULES, Suppression, suppressed_regions};
+    use crate::config::RuleName;
+    use crate::formatter::lexer::tokenize;
+
+    fn regions(input: &str) -> Vec<(Suppression, usize, usize)> {
+        let tokens = tokenize(input);
+        suppressed_regions(&tokens, input)
+            .into_iter()
+            .map(|region| (region.reason, region.start_line, region.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn off_on_and_ignore_next_line_produce_regions() {
+        let input = "get-item .\n# pwshfmt: off\n$a   = 1\n# pwshfmt: on\n# pwshfmt-ignore-next-line\n$b   = 2\n# pwshfmt: off\n$c = 3\n";
+        assert_eq!(
+            regions(input),
+            vec![
+                (Suppression::Off, 2, 4),
+                (Suppression::IgnoreNextLine, 6, 6),
+                (Suppression::Off, 7, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_header_only_applies_before_code() {
+        assert_eq!(
+            regions("#requires -Version 7\n# pwshfmt: skip\nget-item .\n"),
+            vec![(Suppression::File, 1, 3)]
+        );
+        assert!(regions("get-item .\n# pwshfmt: skip\n").is_empty());
+    }
+
+    #[test]
+    fn suppress_message_attribute_covers_the_enclosing_function() {
+        let input = "function Test {\n    [Diagnostics.CodeAnalysis.SuppressMessageAttribute('pwshfmt', '')]\n    param()\n    get-item .\n}\n[SuppressMessage('PSAvoidUsingWriteHost', '')]\nparam()\n";
+        assert_eq!(regions(input), vec![(Suppression::SuppressMessage, 2, 4)]);
+    }
+
+    #[test]
+    fn script_analyzer_rule_names_map_to_formatter_rules() {
+        let rules = |rule: &str| {
+            let input =
+                format!("function Test {{\n    [SuppressMessage('{rule}', '')]\n    param()\n}}\n");
+            let tokens = tokenize(&input);
+            suppressed_regions(&tokens, &input)
+                .into_iter()
+                .map(|region| region.rules.to_vec())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(rules("pwshfmt"), vec![Vec::new()]);
+        assert_eq!(rules("PSUseCorrectCasing"), vec![CASING_RULES.to_vec()]);
+        for rule in [
+            "PSPlaceOpenBrace",
+            "PSUseConsistentIndentation",
+            "PSUseConsistentWhitespace",
+            "PSAlignAssignmentStatement",
+        ] {
+            assert_eq!(rules(rule), vec![vec![RuleName::Layout]], "{rule}");
+        }
+        assert!(rules("PSAvoidUsingWriteHost").is_empty());
+    }
+}
@@ -0,0 +1,248 @@
+use crate::config::CaseStyle;
+
+// 类型加速器按 PascalCase 记录，实际输出由 `[rules] type_case.style` 决定。
+const ACCELERATORS: [&str; 40] = [
+    "Array",
+    "BigInt",
+    "Bool",
+    "Byte",
+    "Char",
+    "DateTime",
+    "Decimal",
+    "Double",
+    "Float",
+    "Guid",
+    "Hashtable",
+    "Int",
+    "Int16",
+    "Int32",
+    "Int64",
+    "IPAddress",
+    "Long",
+    "Object",
+    "Ordered",
+    "PSCredential",
+    "PSCustomObject",
+    "PSObject",
+    "Ref",
+    "Regex",
+    "SByte",
+    "ScriptBlock",
+    "SecureString",
+    "SemVer",
+    "Short",
+    "Single",
+    "String",
+    "Switch",
+    "TimeSpan",
+    "Type",
+    "UInt16",
+    "UInt32",
+    "UInt64",
+    "Uri",
+    "Version",
+    "Void",
+];
+
+// 完整类型名；`[IO.Path]`、`[Math]` 这类省略命名空间的写法按后缀匹配。
+const TYPES: [&str; 44] = [
+    "System.Activator",
+    "System.AppDomain",
+    "System.BitConverter",
+    "System.Boolean",
+    "System.Collections.ArrayList",
+    "System.Collections.Generic.Dictionary",
+    "System.Collections.Generic.HashSet",
+    "System.Collections.Generic.List",
+    "System.Collections.Specialized.OrderedDictionary",
+    "System.Console",
+    "System.ConsoleColor",
+    "System.Convert",
+    "System.DateTimeOffset",
+    "System.Diagnostics.CodeAnalysis.SuppressMessageAttribute",
+    "System.Diagnostics.Process",
+    "System.Diagnostics.ProcessStartInfo",
+    "System.Diagnostics.Stopwatch",
+    "System.Enum",
+    "System.Environment",
+    "System.EnvironmentVariableTarget",
+    "System.Globalization.CultureInfo",
+    "System.Int32",
+    "System.IO.Directory",
+    "System.IO.DirectoryInfo",
+    "System.IO.File",
+    "System.IO.FileInfo",
+    "System.IO.Path",
+    "System.IO.SearchOption",
+    "System.Management.Automation.ErrorRecord",
+    "System.Management.Automation.Language.Parser",
+    "System.Math",
+    "System.Net.Http.HttpClient",
+    "System.Net.WebClient",
+    "System.Object",
+    "System.PlatformID",
+    "System.Runtime.InteropServices.RuntimeInformation",
+    "System.String",
+    "System.StringComparer",
+    "System.StringComparison",
+    "System.StringSplitOptions",
+    "System.Text.Encoding",
+    "System.Text.RegularExpressions.RegexOptions",
+    "System.Text.StringBuilder",
+    "System.Threading.Thread",
+];
+
+const ATTRIBUTES: [&str; 20] = [
+    "Alias",
+    "AllowEmptyCollection",
+    "AllowEmptyString",
+    "AllowNull",
+    "ArgumentCompleter",
+    "ArgumentCompletions",
+    "CmdletBinding",
+    "DscProperty",
+    "DscResource",
+    "OutputType",
+    "Parameter",
+    "SupportsWildcards",
+    "ValidateCount",
+    "ValidateLength",
+    "ValidateNotNull",
+    "ValidateNotNullOrEmpty",
+    "ValidatePattern",
+    "ValidateRange",
+    "ValidateScript",
+    "ValidateSet",
+];
+
+const ATTRIBUTE_ARGUMENTS: [&str; 24] = [
+    "Category",
+    "CheckId",
+    "ConfirmImpact",
+    "DefaultParameterSetName",
+    "DontShow",
+    "ErrorMessage",
+    "HelpMessage",
+    "HelpUri",
+    "IgnoreCase",
+    "Justification",
+    "Mandatory",
+    "MessageId",
+    "ParameterSetName",
+    "Position",
+    "PositionalBinding",
+    "RemotingCapability",
+    "Scope",
+    "SupportsPaging",
+    "SupportsShouldProcess",
+    "SupportsTransactions",
+    "Target",
+    "ValueFromPipeline",
+    "ValueFromPipelineByPropertyName",
+    "ValueFromRemainingArguments",
+];
+
+pub fn canonical_type(name: &str, accelerator_case: CaseStyle) -> Option<String> {
+    if let Some(accelerator) = find(&ACCELERATORS, name) {
+        return Some(match accelerator_case {
+            CaseStyle::Lower => accelerator.to_ascii_lowercase(),
+            CaseStyle::Upper => accelerator.to_ascii_uppercase(),
+            CaseStyle::Pascal => accelerator.to_string(),
+            CaseStyle::Preserve => name.to_string(),
+        });
+    }
+
+    full_type_suffix(name).map(ToOwned::to_owned)
+}
+
+// 特性名可以省略 `Attribute` 后缀：`[Diagnostics.CodeAnalysis.SuppressMessage()]`。
+pub fn canonical_attribute(name: &str) -> Option<String> {
+    if let Some(attribute) = find(&ATTRIBUTES, name) {
+        return Some(attribute.to_string());
+    }
+    if let Some(full) = full_type_suffix(name) {
+        return Some(full.to_string());
+    }
+
+    let with_suffix = format!("{name}Attribute");
+    full_type_suffix(&with_suffix).map(|full| full.trim_end_matches("Attribute").to_string())
+}
+
+pub fn canonical_attribute_argument(name: &str) -> Option<&'static str> {
+    find(&ATTRIBUTE_ARGUMENTS, name)
+}
+
+fn find(table: &[&'static str], name: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|candidate| candidate.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+fn full_type_suffix(name: &str) -> Option<&'static str> {
+    TYPES.iter().find_map(|full| {
+        if full.eq_ignore_ascii_case(name) {
+            return Some(*full);
+        }
+        let suffix = full.get(full.len().checked_sub(name.len())?..)?;
+        let boundary = full.len() - name.len();
+        (boundary > 0 && full.as_bytes()[boundary - 1] == b'.' && suffix.eq_ignore_ascii_case(name))
+            .then_some(suffix)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_attribute, canonical_attribute_argument, canonical_type};
+    use crate::config::CaseStyle;
+
+    #[test]
+    fn resolves_accelerators_with_configured_style() {
+        assert_eq!(
+            canonical_type("STRING", CaseStyle::Lower).as_deref(),
+            Some("string")
+        );
+        assert_eq!(
+            canonical_type("pscustomobject", CaseStyle::Pascal).as_deref(),
+            Some("PSCustomObject")
+        );
+        assert_eq!(
+            canonical_type("Int", CaseStyle::Preserve).as_deref(),
+            Some("Int")
+        );
+    }
+
+    #[test]
+    fn resolves_full_and_partial_type_names() {
+        assert_eq!(
+            canonical_type("system.io.path", CaseStyle::Lower).as_deref(),
+            Some("System.IO.Path")
+        );
+        assert_eq!(
+            canonical_type("io.path", CaseStyle::Lower).as_deref(),
+            Some("IO.Path")
+        );
+        assert_eq!(
+            canonical_type("math", CaseStyle::Lower).as_deref(),
+            Some("Math")
+        );
+        assert_eq!(canonical_type("ath", CaseStyle::Lower), None);
+        assert_eq!(canonical_type("My.Custom.Type", CaseStyle::Lower), None);
+    }
+
+    #[test]
+    fn resolves_attributes_and_named_arguments() {
+        assert_eq!(
+            canonical_attribute("cmdletbinding").as_deref(),
+            Some("CmdletBinding")
+        );
+        assert_eq!(
+            canonical_attribute("diagnostics.codeanalysis.suppressmessage").as_deref(),
+            Some("Diagnostics.CodeAnalysis.SuppressMessage")
+        );
+        assert_eq!(
+            canonical_attribute_argument("valuefrompipeline"),
+            Some("ValueFromPipeline")
+        );
+    }
+}
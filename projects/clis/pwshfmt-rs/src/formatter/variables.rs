@@ -0,0 +1,130 @@
+// 自动变量与首选项变量的规范写法；`$sender`、`$event` 这类常被当作普通参数名的条目不收录。
+const AUTOMATIC_VARIABLES: [&str; 46] = [
+    "args",
+    "ConfirmPreference",
+    "DebugPreference",
+    "Error",
+    "ErrorActionPreference",
+    "ErrorView",
+    "ExecutionContext",
+    "false",
+    "FormatEnumerationLimit",
+    "HOME",
+    "Host",
+    "InformationPreference",
+    "input",
+    "IsCoreCLR",
+    "IsLinux",
+    "IsMacOS",
+    "IsWindows",
+    "LASTEXITCODE",
+    "Matches",
+    "MaximumHistoryCount",
+    "MyInvocation",
+    "NestedPromptLevel",
+    "null",
+    "OFS",
+    "OutputEncoding",
+    "PID",
+    "PROFILE",
+    "ProgressPreference",
+    "PSBoundParameters",
+    "PSCmdlet",
+    "PSCommandPath",
+    "PSCulture",
+    "PSDefaultParameterValues",
+    "PSEdition",
+    "PSHOME",
+    "PSItem",
+    "PSNativeCommandUseErrorActionPreference",
+    "PSScriptRoot",
+    "PSStyle",
+    "PSUICulture",
+    "PSVersionTable",
+    "PWD",
+    "this",
+    "true",
+    "VerbosePreference",
+    "WarningPreference",
+];
+
+// 作用域修饰符与驱动器限定符统一为小写：`$script:`、`$env:`。
+const QUALIFIERS: [&str; 10] = [
+    "alias", "env", "function", "global", "local", "private", "script", "using", "variable",
+    "workflow",
+];
+
+// 返回 `$name`/`@name` 的规范写法；用户变量、环境变量名及 `${...}` 写法保持原样。
+pub fn canonical_variable(text: &str) -> Option<String> {
+    let sigil = text.get(..1)?;
+    let body = &text[1..];
+    if body.starts_with('{') {
+        return None;
+    }
+
+    let (qualifier, name) = match body.split_once(':') {
+        Some((qualifier, name)) => {
+            let canonical = QUALIFIERS
+                .iter()
+                .find(|candidate| candidate.eq_ignore_ascii_case(qualifier))?;
+            (Some(*canonical), name)
+        }
+        None => (None, body),
+    };
+
+    let name = match qualifier {
+        Some("env" | "function" | "alias") => name,
+        _ => AUTOMATIC_VARIABLES
+            .iter()
+            .find(|candidate| candidate.eq_ignore_ascii_case(name))
+            .copied()
+            .unwrap_or(name),
+    };
+
+    Some(match qualifier {
+        Some(qualifier) => format!("{sigil}{qualifier}:{name}"),
+        None => format!("{sigil}{name}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::canonical_variable;
+
+    #[test]
+    fn normalizes_automatic_variables_and_qualifiers() {
+        assert_eq!(canonical_variable("$NULL").as_deref(), Some("$null"));
+        assert_eq!(
+            canonical_variable("$psscriptroot").as_deref(),
+            Some("$PSScriptRoot")
+        );
+        assert_eq!(
+            canonical_variable("@psboundparameters").as_deref(),
+            Some("@PSBoundParameters")
+        );
+        assert_eq!(
+            canonical_variable("$Env:Path").as_deref(),
+            Some("$env:Path")
+        );
+        assert_eq!(
+            canonical_variable("$Global:errorActionPreference").as_deref(),
+            Some("$global:ErrorActionPreference")
+        );
+        assert_eq!(
+            canonical_variable("$Script:myValue").as_deref(),
+            Some("$script:myValue")
+        );
+    }
+
+    #[test]
+    fn leaves_user_variables_untouched() {
+        assert_eq!(canonical_variable("$myPath").as_deref(), Some("$myPath"));
+        assert_eq!(canonical_variable("$script").as_deref(), Some("$script"));
+        assert_eq!(
+            canonical_variable("$Env:null").as_deref(),
+            Some("$env:null")
+        );
+        assert_eq!(canonical_variable("${Env:Path}"), None);
+        assert_eq!(canonical_variable("$c:temp"), None);
+    }
+}
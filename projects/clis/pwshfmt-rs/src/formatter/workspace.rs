@@ -0,0 +1,152 @@
+use super::dictionary::DictionaryFile;
+use super::lexer::{Token, TokenKind, tokenize};
+
+// 只收录 Verb-Noun 形式的函数名，避免 `main`、`build` 这类短名误伤同名裸词。
+pub fn learn_functions(input: &str) -> DictionaryFile {
+    let tokens: Vec<Token> = tokenize(input)
+        .into_iter()
+        .filter(|token| !token.kind.is_trivia() && token.kind != TokenKind::NewLine)
+        .collect();
+    let mut file = DictionaryFile::default();
+
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Word || !is_function_keyword(token.text(input)) {
+            continue;
+        }
+
+        let Some(name_token) = tokens
+            .get(index + 1)
+            .filter(|next| next.kind == TokenKind::Word)
+        else {
+            continue;
+        };
+        let name = name_token.text(input);
+        if !name.contains('-') || name.starts_with('-') || name.ends_with('-') {
+            continue;
+        }
+
+        let parameters = find_parameter_block(&tokens, index + 2, input)
+            .map(|start| collect_parameter_names(&tokens, start, input))
+            .unwrap_or_default();
+        file.command_parameters
+            .entry(name.to_string())
+            .or_default()
+            .extend(parameters);
+    }
+
+    file.normalize();
+    file
+}
+
+fn is_function_keyword(text: &str) -> bool {
+    text.eq_ignore_ascii_case("function")
+        || text.eq_ignore_ascii_case("filter")
+        || text.eq_ignore_ascii_case("workflow")
+}
+
+// 返回参数列表左括号的位置：`function Foo($a)` 或函数体内首个 `param(`。
+fn find_parameter_block(tokens: &[Token], start: usize, input: &str) -> Option<usize> {
+    match tokens.get(start)?.kind {
+        TokenKind::LParen => Some(start),
+        TokenKind::LBrace => {
+            let mut depth = 0usize;
+            for (offset, token) in tokens[start..].iter().enumerate() {
+                match token.kind {
+                    TokenKind::LBrace | TokenKind::HashtableStart => depth += 1,
+                    TokenKind::RBrace => {
+                        depth = depth.saturating_sub(1);
+                        if depth == 0 {
+                            return None;
+                        }
+                    }
+                    TokenKind::Word
+                        if depth == 1 && token.text(input).eq_ignore_ascii_case("param") =>
+                    {
+                        let next = start + offset + 1;
+                        return tokens
+                            .get(next)
+                            .filter(|candidate| candidate.kind == TokenKind::LParen)
+                            .map(|_| next);
+                    }
+                    _ => {}
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+fn collect_parameter_names(tokens: &[Token], open_paren: usize, input: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut paren_depth = 0usize;
+    let mut bracket_depth = 0usize;
+    let mut expect_name = true;
+
+    for token in &tokens[open_paren..] {
+        match token.kind {
+            TokenKind::LParen | TokenKind::SubExpressionStart | TokenKind::ArrayExpressionStart => {
+                paren_depth += 1;
+            }
+            TokenKind::RParen => {
+                paren_depth = paren_depth.saturating_sub(1);
+                if paren_depth == 0 {
+                    break;
+                }
+            }
+            TokenKind::LBracket => bracket_depth += 1,
+            TokenKind::RBracket => bracket_depth = bracket_depth.saturating_sub(1),
+            TokenKind::Comma if paren_depth == 1 && bracket_depth == 0 => expect_name = true,
+            TokenKind::Variable if paren_depth == 1 && bracket_depth == 0 && expect_name => {
+                let name = token.text(input).trim_start_matches('$');
+                if !name.contains(':') && !name.starts_with('{') {
+                    names.push(name.to_string());
+                }
+                expect_name = false;
+            }
+            _ => {}
+        }
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::learn_functions;
+
+    #[test]
+    fn learns_function_names_and_param_block_variables() {
+        let input = r#"
+function Get-RepoRoot {
+    [CmdletBinding()]
+    param(
+        [Parameter(Mandatory)][string]$StartPath,
+        [switch]$NoCache = $false,
+        [ValidateSet('a', 'b')]
+        $Mode
+    )
+    $inner = @{ a = 1 }
+}
+
+function ConvertTo-Slug([string]$Text, $Separator = '-') { $Text }
+function helper { param($Ignored) }
+"#;
+
+        let file = learn_functions(input);
+
+        assert_eq!(file.commands, vec!["ConvertTo-Slug", "Get-RepoRoot"]);
+        assert_eq!(
+            file.command_parameters.get("Get-RepoRoot"),
+            Some(&vec![
+                "Mode".to_string(),
+                "NoCache".to_string(),
+                "StartPath".to_string()
+            ])
+        );
+        assert_eq!(
+            file.command_parameters.get("ConvertTo-Slug"),
+            Some(&vec!["Separator".to_string(), "Text".to_string()])
+        );
+    }
+}
@@ -1,6 +1,8 @@
 pub mod cli;
 pub mod config;
+pub mod dict;
 pub mod discovery;
+pub mod encoding;
 pub mod error;
 pub mod formatter;
 pub mod processor;
@@ -10,10 +12,10 @@ use std::path::Path;
 
 use clap::Parser;
 
-use crate::cli::Cli;
+use crate::cli::{Cli, Commands};
 use crate::error::{AppError, Result};
 use crate::processor::{FallbackRunner, PwshFallbackRunner, resolve_fallback_script_path};
-use crate::summary::Summary;
+use crate::summary::{RunMode, Summary};
 
 pub fn run() -> Result<i32> {
     let cli = Cli::parse();
@@ -21,18 +23,30 @@ pub fn run() -> Result<i32> {
 }
 
 pub fn run_with_cli(cli: Cli) -> Result<i32> {
-    let cwd = std::env::current_dir().map_err(|source| AppError::io("读取当前目录", ".", source))?;
-    let mode = cli.run_mode();
+    let cwd =
+        std::env::current_dir().map_err(|source| AppError::io("读取当前目录", ".", source))?;
+    let mode = match &cli.command {
+        Commands::Dict(command) => return dict::run(command, &cli, &cwd),
+        Commands::Check => RunMode::Check,
+        Commands::Write => RunMode::Write,
+    };
     let config = config::load(&cli, &cwd)?;
 
-    let fallback_runner = PwshFallbackRunner::new(resolve_fallback_script_path(&cwd, &config), cwd.clone());
+    let fallback_runner =
+        PwshFallbackRunner::new(resolve_fallback_script_path(&cwd, &config), cwd.clone());
     let summary = processor::run(mode, &config, &cwd, &fallback_runner)?;
 
-    Ok(summary.exit_code(mode))
+    Ok(summary.exit_code(mode, config.effective_unsafe_policy()))
 }
 
-pub fn run_with_runner(cli: &Cli, cwd: &Path, fallback_runner: &dyn FallbackRunner) -> Result<Summary> {
-    let mode = cli.run_mode();
+pub fn run_with_runner(
+    cli: &Cli,
+    cwd: &Path,
+    fallback_runner: &dyn FallbackRunner,
+) -> Result<Summary> {
+    let mode = cli
+        .run_mode()
+        .ok_or_else(|| AppError::invalid_arguments("仅 check/write 子命令可执行格式化"))?;
     let config = config::load(cli, cwd)?;
     processor::run(mode, &config, cwd, fallback_runner)
 }
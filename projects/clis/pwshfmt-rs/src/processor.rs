@@ -5,11 +5,17 @@ use std::process::Command;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::config::{Config, FALLBACK_ACTIVE_ENV};
-use crate::discovery::discover_files;
+use globset::GlobMatcher;
+
+use crate::config::{Config, FALLBACK_ACTIVE_ENV, UnsafePolicy};
+use crate::discovery::{compile_glob, discover_files, glob_matches};
+use crate::encoding::{self, TextEncoding, read_text};
 use crate::error::{AppError, Result};
-use crate::formatter::format_content;
-use crate::summary::{FileReport, FileStatus, RunMode, Summary};
+use crate::formatter::line_endings::{count_line_endings, normalize as normalize_newlines};
+use crate::formatter::safety::UnsafeKind;
+use crate::formatter::workspace::learn_functions;
+use crate::formatter::{FormatOptions, FormatOutcome, format_content_allowing};
+use crate::summary::{FileReport, FileStatus, FixCounts, RunMode, Summary};
 
 static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
 
@@ -69,6 +75,53 @@ impl FallbackRunner for PwshFallbackRunner {
     }
 }
 
+// `unsafe_allow` 中按路径 glob 放行的种类；`paths` 为空的条目作用于所有文件。
+struct UnsafeAllowlist {
+    entries: Vec<(Vec<GlobMatcher>, Vec<UnsafeKind>)>,
+    cwd: PathBuf,
+}
+
+impl UnsafeAllowlist {
+    fn compile(config: &Config, cwd: &Path) -> Result<Self> {
+        let entries = config
+            .unsafe_allow
+            .iter()
+            .map(|entry| {
+                let matchers = entry
+                    .paths
+                    .iter()
+                    .map(|pattern| compile_glob(pattern))
+                    .collect::<Result<Vec<_>>>()?;
+                let kinds = if entry.kinds.is_empty() {
+                    UnsafeKind::ALL.to_vec()
+                } else {
+                    entry.kinds.clone()
+                };
+                Ok((matchers, kinds))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            entries,
+            cwd: cwd.to_path_buf(),
+        })
+    }
+
+    fn kinds_for(&self, path: &Path) -> Vec<UnsafeKind> {
+        let mut kinds = Vec::new();
+        for (matchers, allowed) in &self.entries {
+            if matchers.is_empty()
+                || matchers
+                    .iter()
+                    .any(|matcher| glob_matches(matcher, path, &self.cwd))
+            {
+                kinds.extend(allowed.iter().copied());
+            }
+        }
+        kinds
+    }
+}
+
 pub fn resolve_fallback_script_path(cwd: &Path, config: &Config) -> PathBuf {
     if config.fallback_script.is_absolute() {
         config.fallback_script.clone()
@@ -89,16 +142,22 @@ pub fn run(
         return Ok(Summary::default());
     }
 
+    let mut options = FormatOptions::from_config(config, cwd)?;
+    let allowlist = UnsafeAllowlist::compile(config, cwd)?;
+    if config.learn_workspace_functions {
+        learn_workspace_functions(&files, &mut options);
+    }
+
     println!(
-        "INFO mode={mode:?}, files={}, strict_fallback={}",
+        "INFO mode={mode:?}, files={}, unsafe_policy={:?}",
         files.len(),
-        config.strict_fallback
+        config.effective_unsafe_policy()
     );
 
     let mut summary = Summary::default();
 
     for path in files {
-        let report = process_file(&path, mode, config, fallback_runner);
+        let report = process_file(&path, mode, config, &options, &allowlist, fallback_runner);
         print_file_report(&report);
         summary.track(&report);
     }
@@ -107,85 +166,157 @@ pub fn run(
     Ok(summary)
 }
 
+// 读取失败的文件留给 process_file 统一报告，预扫描阶段直接跳过。
+fn learn_workspace_functions(files: &[PathBuf], options: &mut FormatOptions) {
+    for path in files {
+        if let Ok((content, _)) = read_text(path) {
+            options
+                .workspace_functions
+                .merge(&learn_functions(&content));
+        }
+    }
+
+    println!(
+        "INFO 已学习工作区函数定义: {}",
+        options.workspace_functions.command_count()
+    );
+}
+
 fn process_file(
     path: &Path,
     mode: RunMode,
     config: &Config,
+    options: &FormatOptions,
+    allowlist: &UnsafeAllowlist,
     fallback_runner: &dyn FallbackRunner,
 ) -> FileReport {
-    let original = match fs::read_to_string(path) {
+    let (original, detected) = match read_text(path) {
         Ok(value) => value,
         Err(error) => {
             return FileReport::failed(path.to_path_buf(), format!("读取文件失败: {error}"));
         }
     };
 
-    let correction = format_content(&original);
+    let correction = format_content_allowing(&original, options, &allowlist.kinds_for(path));
+    format_file(
+        path,
+        &original,
+        detected,
+        mode,
+        config,
+        correction,
+        fallback_runner,
+    )
+    .with_line_endings(count_line_endings(&original))
+}
 
-    if correction.unsafe_detected {
-        if !config.strict_fallback {
-            eprintln!("SKIPPED {} (检测到不安全语法，跳过格式化)", path.display());
-            return FileReport::unchanged(path.to_path_buf(), 0, 0);
-        }
+fn format_file(
+    path: &Path,
+    original: &str,
+    detected: TextEncoding,
+    mode: RunMode,
+    config: &Config,
+    mut correction: FormatOutcome,
+    fallback_runner: &dyn FallbackRunner,
+) -> FileReport {
+    if correction.file_suppressed() {
+        return FileReport::unchanged(path.to_path_buf(), FixCounts::default())
+            .with_suppressed_regions(correction.suppressed_regions);
+    }
+
+    let findings = std::mem::take(&mut correction.unsafe_findings);
+
+    let policy = config.effective_unsafe_policy();
+    if !findings.is_empty() && matches!(policy, UnsafePolicy::Skip | UnsafePolicy::Fail) {
+        return FileReport::skipped(path.to_path_buf()).with_unsafe_findings(findings);
+    }
 
-        return match mode {
-            RunMode::Check => match run_fallback_check(path, &original, fallback_runner) {
+    if !findings.is_empty() && policy == UnsafePolicy::Fallback {
+        let report = match mode {
+            RunMode::Check => match run_fallback_check(path, original, detected, fallback_runner) {
                 Ok(changed) if changed => {
-                    FileReport::needs_fix(path.to_path_buf(), 0, 0).with_fallback(true)
+                    FileReport::needs_fix(path.to_path_buf(), FixCounts::default())
+                        .with_fallback(true)
                 }
-                Ok(_) => FileReport::unchanged(path.to_path_buf(), 0, 0).with_fallback(false),
+                Ok(_) => FileReport::unchanged(path.to_path_buf(), FixCounts::default())
+                    .with_fallback(false),
                 Err(error) => {
                     FileReport::failed(path.to_path_buf(), format!("严格回退失败: {error}"))
                 }
             },
-            RunMode::Write => match run_fallback_write(path, &original, fallback_runner) {
+            RunMode::Write => match run_fallback_write(path, original, fallback_runner) {
                 Ok(changed) if changed => {
-                    FileReport::updated(path.to_path_buf(), 0, 0).with_fallback(true)
+                    FileReport::updated(path.to_path_buf(), FixCounts::default())
+                        .with_fallback(true)
                 }
-                Ok(_) => FileReport::unchanged(path.to_path_buf(), 0, 0).with_fallback(false),
+                Ok(_) => FileReport::unchanged(path.to_path_buf(), FixCounts::default())
+                    .with_fallback(false),
                 Err(error) => {
                     FileReport::failed(path.to_path_buf(), format!("严格回退失败: {error}"))
                 }
             },
         };
+        return report.with_unsafe_findings(findings);
     }
 
-    if correction.formatted == original {
-        return FileReport::unchanged(
-            path.to_path_buf(),
-            correction.command_fixes,
-            correction.parameter_fixes,
-        );
+    let newlines = normalize_newlines(
+        &correction.formatted,
+        config.line_ending,
+        config.insert_final_newline,
+    );
+    let mut target = TextEncoding::resolve(config.encoding, detected);
+    let missing_bom_line =
+        if config.bom_for_non_ascii && target == TextEncoding::Utf8 && encoding::requires_bom(path)
+        {
+            encoding::first_non_ascii_line(&newlines.formatted)
+        } else {
+            None
+        };
+    let mut fixes = correction.fix_counts();
+    let skipped = correction.unsafe_regions;
+    let suppressed = correction.suppressed_regions;
+    fixes.line_ending = newlines.line_ending_fixes;
+    fixes.final_newline = newlines.final_newline_fixes;
+    if missing_bom_line.is_some() {
+        target = TextEncoding::Utf8Bom;
+        fixes.bom = 1;
+    } else {
+        fixes.encoding = usize::from(target != detected);
+    }
+    if newlines.formatted == original && target == detected {
+        return FileReport::unchanged(path.to_path_buf(), fixes)
+            .with_skipped_regions(skipped)
+            .with_suppressed_regions(suppressed)
+            .with_unsafe_findings(findings);
     }
 
-    match mode {
-        RunMode::Check => FileReport::needs_fix(
-            path.to_path_buf(),
-            correction.command_fixes,
-            correction.parameter_fixes,
-        ),
-        RunMode::Write => match fs::write(path, correction.formatted.as_bytes()) {
-            Ok(()) => FileReport::updated(
-                path.to_path_buf(),
-                correction.command_fixes,
-                correction.parameter_fixes,
-            ),
+    let report = match mode {
+        RunMode::Check => FileReport::needs_fix(path.to_path_buf(), fixes),
+        RunMode::Write => match fs::write(path, encoding::encode(&newlines.formatted, target)) {
+            Ok(()) => FileReport::updated(path.to_path_buf(), fixes),
             Err(error) => FileReport::failed(path.to_path_buf(), format!("写回失败: {error}")),
         },
-    }
+    };
+    report
+        .with_missing_bom(missing_bom_line)
+        .with_skipped_regions(skipped)
+        .with_suppressed_regions(suppressed)
+        .with_unsafe_findings(findings)
 }
 
 fn run_fallback_check(
     path: &Path,
     original: &str,
+    detected: TextEncoding,
     fallback_runner: &dyn FallbackRunner,
 ) -> Result<bool> {
     let temp_file = build_temp_path(path);
-    fs::write(&temp_file, original.as_bytes())
+    fs::write(&temp_file, encoding::encode(original, detected))
         .map_err(|source| AppError::io("写入临时文件", &temp_file, source))?;
 
     let run_result = fallback_runner.run_strict(&temp_file);
-    let formatted_result = fs::read_to_string(&temp_file)
+    let formatted_result = read_text(&temp_file)
+        .map(|(text, _)| text)
         .map_err(|source| AppError::io("读取临时文件", &temp_file, source));
 
     if let Err(error) = fs::remove_file(&temp_file) {
@@ -204,8 +335,8 @@ fn run_fallback_write(
     fallback_runner: &dyn FallbackRunner,
 ) -> Result<bool> {
     fallback_runner.run_strict(path)?;
-    let formatted =
-        fs::read_to_string(path).map_err(|source| AppError::io("读取回退结果", path, source))?;
+    let (formatted, _) =
+        read_text(path).map_err(|source| AppError::io("读取回退结果", path, source))?;
     Ok(formatted != original)
 }
 
@@ -230,16 +361,93 @@ fn print_file_report(report: &FileReport) {
         FileStatus::NeedsFix => "NEEDS_FIX",
         FileStatus::Updated => "UPDATED",
         FileStatus::Failed => "FAILED",
+        FileStatus::Skipped => "SKIPPED",
     };
 
+    if let Some(counts) = report.mixed_line_endings {
+        eprintln!(
+            "WARN {} (混合换行符: crlf={}, lf={})",
+            report.path.display(),
+            counts.crlf,
+            counts.lf
+        );
+    }
+
+    if let Some(line) = report.missing_bom_line {
+        let action = if report.status == FileStatus::Updated {
+            "已添加 UTF-8 BOM"
+        } else {
+            "缺少 UTF-8 BOM"
+        };
+        eprintln!(
+            "WARN {} (第 {line} 行含非 ASCII 字符，{action})",
+            report.path.display()
+        );
+    }
+
+    for finding in &report.unsafe_findings {
+        eprintln!(
+            "UNSAFE {}:{}:{} [{}] {}",
+            report.path.display(),
+            finding.line,
+            finding.column,
+            finding.kind.name(),
+            finding.snippet
+        );
+    }
+
+    if !report.skipped_regions.is_empty() {
+        // 同一行的多个子表达式只报告一次。
+        let mut spans: Vec<String> = report
+            .skipped_regions
+            .iter()
+            .map(|region| {
+                if region.start_line == region.end_line {
+                    format!("第 {} 行", region.start_line)
+                } else {
+                    format!("第 {}-{} 行", region.start_line, region.end_line)
+                }
+            })
+            .collect();
+        spans.dedup();
+        eprintln!(
+            "PARTIAL {} (检测到不安全语法，跳过: {})",
+            report.path.display(),
+            spans.join(", ")
+        );
+    }
+
+    if !report.suppressed_regions.is_empty() {
+        let spans: Vec<String> = report
+            .suppressed_regions
+            .iter()
+            .map(|region| {
+                let mut reason = region.reason.name().to_string();
+                if !region.suppresses_all() {
+                    let rules: Vec<&str> = region.rules.iter().map(|rule| rule.name()).collect();
+                    reason = format!("{reason}: {}", rules.join(", "));
+                }
+                if region.start_line == region.end_line {
+                    format!("第 {} 行 [{reason}]", region.start_line)
+                } else {
+                    format!("第 {}-{} 行 [{reason}]", region.start_line, region.end_line)
+                }
+            })
+            .collect();
+        println!(
+            "SUPPRESSED {} (按注释跳过: {})",
+            report.path.display(),
+            spans.join(", ")
+        );
+    }
+
     if let Some(detail) = &report.detail {
         eprintln!("{status} {} ({detail})", report.path.display());
     } else {
         println!(
-            "{status} {} (command_fixes={}, parameter_fixes={}, fallback={})",
+            "{status} {} ({}, fallback={})",
             report.path.display(),
-            report.command_fixes,
-            report.parameter_fixes,
+            format_fix_counts(&report.fixes),
             report.fallback_invoked
         );
     }
@@ -247,15 +455,47 @@ fn print_file_report(report: &FileReport) {
 
 fn print_summary(summary: &Summary) {
     println!(
-        "SUMMARY total={} unchanged={} needs_fix={} updated={} failed={} fallback_invoked={} fallback_changed={} command_fixes={} parameter_fixes={}",
+        "SUMMARY total={} unchanged={} needs_fix={} updated={} failed={} skipped={} fallback_invoked={} fallback_changed={} mixed_line_endings={} partial={} suppressed={} {}",
         summary.total,
         summary.unchanged,
         summary.needs_fix,
         summary.updated,
         summary.failed,
+        summary.skipped,
         summary.fallback_invoked,
         summary.fallback_changed,
-        summary.command_fixes,
-        summary.parameter_fixes
+        summary.mixed_line_endings,
+        summary.partial,
+        summary.suppressed,
+        format_fix_counts(&summary.fixes).replace(", ", " ")
     );
+
+    if !summary.unsafe_findings.is_empty() {
+        let counts: Vec<String> = summary
+            .unsafe_findings
+            .iter()
+            .map(|(kind, count)| format!("{}={count}", kind.name()))
+            .collect();
+        println!("SUMMARY unsafe_findings {}", counts.join(" "));
+    }
+}
+
+fn format_fix_counts(fixes: &FixCounts) -> String {
+    format!(
+        "command_fixes={}, function_fixes={}, parameter_fixes={}, keyword_fixes={}, operator_fixes={}, type_fixes={}, variable_fixes={}, alias_fixes={}, member_fixes={}, layout_fixes={}, line_ending_fixes={}, final_newline_fixes={}, encoding_fixes={}, bom_fixes={}",
+        fixes.command,
+        fixes.function,
+        fixes.parameter,
+        fixes.keyword,
+        fixes.operator,
+        fixes.type_name,
+        fixes.variable,
+        fixes.alias,
+        fixes.member,
+        fixes.layout,
+        fixes.line_ending,
+        fixes.final_newline,
+        fixes.encoding,
+        fixes.bom
+    )
 }
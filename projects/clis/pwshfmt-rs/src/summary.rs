@@ -1,5 +1,11 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use crate::config::UnsafePolicy;
+use crate::formatter::line_endings::LineEndingCounts;
+use crate::formatter::safety::{UnsafeFinding, UnsafeKind, UnsafeRegion};
+use crate::formatter::suppression::SuppressedRegion;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RunMode {
     Check,
@@ -12,6 +18,44 @@ pub enum FileStatus {
     NeedsFix,
     Updated,
     Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FixCounts {
+    pub command: usize,
+    pub function: usize,
+    pub parameter: usize,
+    pub keyword: usize,
+    pub operator: usize,
+    pub type_name: usize,
+    pub variable: usize,
+    pub alias: usize,
+    pub member: usize,
+    pub layout: usize,
+    pub line_ending: usize,
+    pub final_newline: usize,
+    pub encoding: usize,
+    pub bom: usize,
+}
+
+impl FixCounts {
+    pub fn add(&mut self, other: &FixCounts) {
+        self.command += other.command;
+        self.function += other.function;
+        self.parameter += other.parameter;
+        self.keyword += other.keyword;
+        self.operator += other.operator;
+        self.type_name += other.type_name;
+        self.variable += other.variable;
+        self.alias += other.alias;
+        self.member += other.member;
+        self.layout += other.layout;
+        self.line_ending += other.line_ending;
+        self.final_newline += other.final_newline;
+        self.encoding += other.encoding;
+        self.bom += other.bom;
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -20,45 +64,77 @@ pub struct FileReport {
     pub status: FileStatus,
     pub fallback_invoked: bool,
     pub fallback_changed: bool,
-    pub command_fixes: usize,
-    pub parameter_fixes: usize,
+    pub fixes: FixCounts,
     pub detail: Option<String>,
+    pub mixed_line_endings: Option<LineEndingCounts>,
+    pub missing_bom_line: Option<usize>,
+    pub skipped_regions: Vec<UnsafeRegion>,
+    pub unsafe_findings: Vec<UnsafeFinding>,
+    pub suppressed_regions: Vec<SuppressedRegion>,
 }
 
 impl FileReport {
-    pub fn unchanged(path: PathBuf, command_fixes: usize, parameter_fixes: usize) -> Self {
+    pub fn unchanged(path: PathBuf, fixes: FixCounts) -> Self {
         Self {
             path,
             status: FileStatus::Unchanged,
             fallback_invoked: false,
             fallback_changed: false,
-            command_fixes,
-            parameter_fixes,
+            fixes,
             detail: None,
+            mixed_line_endings: None,
+            missing_bom_line: None,
+            skipped_regions: Vec::new(),
+            unsafe_findings: Vec::new(),
+            suppressed_regions: Vec::new(),
         }
     }
 
-    pub fn needs_fix(path: PathBuf, command_fixes: usize, parameter_fixes: usize) -> Self {
+    pub fn needs_fix(path: PathBuf, fixes: FixCounts) -> Self {
         Self {
             path,
             status: FileStatus::NeedsFix,
             fallback_invoked: false,
             fallback_changed: false,
-            command_fixes,
-            parameter_fixes,
+            fixes,
             detail: None,
+            mixed_line_endings: None,
+            missing_bom_line: None,
+            skipped_regions: Vec::new(),
+            unsafe_findings: Vec::new(),
+            suppressed_regions: Vec::new(),
         }
     }
 
-    pub fn updated(path: PathBuf, command_fixes: usize, parameter_fixes: usize) -> Self {
+    pub fn updated(path: PathBuf, fixes: FixCounts) -> Self {
         Self {
             path,
             status: FileStatus::Updated,
             fallback_invoked: false,
             fallback_changed: false,
-            command_fixes,
-            parameter_fixes,
+            fixes,
+            detail: None,
+            mixed_line_endings: None,
+            missing_bom_line: None,
+            skipped_regions: Vec::new(),
+            unsafe_findings: Vec::new(),
+            suppressed_regions: Vec::new(),
+        }
+    }
+
+    pub fn skipped(path: PathBuf) -> Self {
+        Self {
+            path,
+            status: FileStatus::Skipped,
+            fallback_invoked: false,
+            fallback_changed: false,
+            fixes: FixCounts::default(),
             detail: None,
+            mixed_line_endings: None,
+            missing_bom_line: None,
+            skipped_regions: Vec::new(),
+            unsafe_findings: Vec::new(),
+            suppressed_regions: Vec::new(),
         }
     }
 
@@ -68,9 +144,13 @@ impl FileReport {
             status: FileStatus::Failed,
             fallback_invoked: false,
             fallback_changed: false,
-            command_fixes: 0,
-            parameter_fixes: 0,
+            fixes: FixCounts::default(),
             detail: Some(detail.into()),
+            mixed_line_endings: None,
+            missing_bom_line: None,
+            skipped_regions: Vec::new(),
+            unsafe_findings: Vec::new(),
+            suppressed_regions: Vec::new(),
         }
     }
 
@@ -79,6 +159,33 @@ impl FileReport {
         self.fallback_changed = changed;
         self
     }
+
+    pub fn with_missing_bom(mut self, line: Option<usize>) -> Self {
+        self.missing_bom_line = line;
+        self
+    }
+
+    pub fn with_skipped_regions(mut self, regions: Vec<UnsafeRegion>) -> Self {
+        self.skipped_regions = regions;
+        self
+    }
+
+    pub fn with_unsafe_findings(mut self, findings: Vec<UnsafeFinding>) -> Self {
+        self.unsafe_findings = findings;
+        self
+    }
+
+    pub fn with_suppressed_regions(mut self, regions: Vec<SuppressedRegion>) -> Self {
+        self.suppressed_regions = regions;
+        self
+    }
+
+    pub fn with_line_endings(mut self, counts: LineEndingCounts) -> Self {
+        if counts.is_mixed() {
+            self.mixed_line_endings = Some(counts);
+        }
+        self
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -88,23 +195,27 @@ pub struct Summary {
     pub needs_fix: usize,
     pub updated: usize,
     pub failed: usize,
+    pub skipped: usize,
     pub fallback_invoked: usize,
     pub fallback_changed: usize,
-    pub command_fixes: usize,
-    pub parameter_fixes: usize,
+    pub mixed_line_endings: usize,
+    pub partial: usize,
+    pub suppressed: usize,
+    pub unsafe_findings: BTreeMap<UnsafeKind, usize>,
+    pub fixes: FixCounts,
 }
 
 impl Summary {
     pub fn track(&mut self, report: &FileReport) {
         self.total += 1;
-        self.command_fixes += report.command_fixes;
-        self.parameter_fixes += report.parameter_fixes;
+        self.fixes.add(&report.fixes);
 
         match report.status {
             FileStatus::Unchanged => self.unchanged += 1,
             FileStatus::NeedsFix => self.needs_fix += 1,
             FileStatus::Updated => self.updated += 1,
             FileStatus::Failed => self.failed += 1,
+            FileStatus::Skipped => self.skipped += 1,
         }
 
         if report.fallback_invoked {
@@ -113,13 +224,30 @@ impl Summary {
         if report.fallback_changed {
             self.fallback_changed += 1;
         }
+        if report.mixed_line_endings.is_some() {
+            self.mixed_line_endings += 1;
+        }
+        if !report.skipped_regions.is_empty() {
+            self.partial += 1;
+        }
+        if !report.suppressed_regions.is_empty() {
+            self.suppressed += 1;
+        }
+        for finding in &report.unsafe_findings {
+            *self.unsafe_findings.entry(finding.kind).or_default() += 1;
+        }
     }
 
-    pub fn exit_code(&self, mode: RunMode) -> i32 {
+    // `unsafe_policy = "fail"` 时被跳过的不安全文件单独以退出码 3 报告，便于 CI 区分。
+    pub fn exit_code(&self, mode: RunMode, policy: UnsafePolicy) -> i32 {
         if self.failed > 0 {
             return 1;
         }
 
+        if policy == UnsafePolicy::Fail && self.skipped > 0 {
+            return 3;
+        }
+
         if mode == RunMode::Check && self.needs_fix > 0 {
             return 2;
         }
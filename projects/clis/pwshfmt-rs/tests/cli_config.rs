@@ -3,7 +3,11 @@ mod common;
 use clap::{Parser, error::ErrorKind};
 use pwshfmt_rs::{
     cli::Cli,
-    config::{self, Config, FALLBACK_ACTIVE_ENV},
+    config::{
+        self, CaseStyle, Config, Encoding, FALLBACK_ACTIVE_ENV, IndentStyle, LineEnding, Rules,
+        UnsafeAllow, UnsafePolicy,
+    },
+    formatter::safety::UnsafeKind,
 };
 
 #[test]
@@ -52,6 +56,18 @@ fn config_uses_defaults_when_file_missing() {
             recurse: false,
             strict_fallback: false,
             fallback_script: std::path::PathBuf::from("fallback.ps1"),
+            unsafe_policy: None,
+            unsafe_allow: Vec::new(),
+            casing_dictionaries: Vec::new(),
+            learn_workspace_functions: false,
+            indent_style: IndentStyle::Space,
+            indent_width: 4,
+            align_assignments: true,
+            line_ending: LineEnding::Preserve,
+            insert_final_newline: false,
+            encoding: Encoding::Preserve,
+            bom_for_non_ascii: false,
+            rules: Rules::default(),
         }
     );
 }
@@ -106,6 +122,246 @@ fallback_script = "from-config-fallback.ps1"
     });
 }
 
+#[test]
+fn keyword_case_is_read_from_file_and_overridden_by_cli() {
+    let workspace = common::create_workspace();
+    common::write_file(
+        workspace.path(),
+        "pwshfmt-rs.toml",
+        "paths = [\"a.ps1\"]\n\n[rules]\nkeyword_case = { style = \"upper\" }\n",
+    );
+
+    let cli = Cli::try_parse_from(["pwshfmt-rs", "check"]).expect("parse cli");
+    let config = config::load(&cli, workspace.path()).expect("load file config");
+    assert_eq!(config.rules.keyword_case.style, CaseStyle::Upper);
+
+    let cli = Cli::try_parse_from(["pwshfmt-rs", "check", "--keyword-case", "pascal"])
+        .expect("parse cli");
+    let config = config::load(&cli, workspace.path()).expect("load cli config");
+    assert_eq!(config.rules.keyword_case.style, CaseStyle::Pascal);
+}
+
+#[test]
+fn cli_shortcuts_override_rules_from_file_and_env() {
+    let workspace = common::create_workspace();
+    common::write_file(
+        workspace.path(),
+        "pwshfmt-rs.toml",
+        r#"
+paths = ["a.ps1"]
+
+[rules]
+keyword_case = { style = "upper" }
+layout = { enabled = true }
+"#,
+    );
+
+    let cli = Cli::try_parse_from([
+        "pwshfmt-rs",
+        "check",
+        "--keyword-case",
+        "pascal",
+        "--layout",
+        "false",
+        "--expand-aliases",
+        "--disable-rule",
+        "alias_expansion",
+    ])
+    .expect("parse cli");
+
+    temp_env::with_vars(
+        [
+            ("PWSHFMT_RS_RULES__KEYWORD_CASE__STYLE", Some("lower")),
+            ("PWSHFMT_RS_RULES__ALIAS_EXPANSION__PLATFORM", Some("true")),
+        ],
+        || {
+            let config = config::load(&cli, workspace.path()).expect("load config");
+            let rules = config.rules;
+
+            assert_eq!(rules.keyword_case.style, CaseStyle::Pascal);
+            assert!(!rules.layout.enabled);
+            // `--disable-rule` 优先于同一层的简写开关。
+            assert!(!rules.alias_expansion.enabled);
+            assert!(rules.alias_expansion.platform);
+        },
+    );
+}
+
+#[test]
+fn bom_rule_conflicts_with_forced_utf8_encoding() {
+    let workspace = common::create_workspace();
+
+    let cli = Cli::try_parse_from([
+        "pwshfmt-rs",
+        "check",
+        "--path",
+        "a.ps1",
+        "--bom-for-non-ascii",
+        "--encoding",
+        "utf8",
+    ])
+    .expect("parse cli");
+    assert!(config::load(&cli, workspace.path()).is_err());
+
+    let cli = Cli::try_parse_from([
+        "pwshfmt-rs",
+        "check",
+        "--path",
+        "a.ps1",
+        "--bom-for-non-ascii",
+        "--encoding",
+        "utf16-le",
+    ])
+    .expect("parse cli");
+    let config = config::load(&cli, workspace.path()).expect("load compatible config");
+    assert_eq!(config.encoding, Encoding::Utf16Le);
+}
+
+#[test]
+fn unsafe_policy_is_read_from_cli_and_legacy_strict_fallback_maps_to_fallback() {
+    let workspace = common::create_workspace();
+    common::write_file(workspace.path(), "fallback.ps1", "# fallback");
+    common::write_file(
+        workspace.path(),
+        "pwshfmt-rs.toml",
+        "paths = [\"a.ps1\"]\nstrict_fallback = true\nfallback_script = \"fallback.ps1\"\n",
+    );
+
+    let cli = Cli::try_parse_from(["pwshfmt-rs", "check"]).expect("parse cli");
+    let config = config::load(&cli, workspace.path()).expect("load legacy config");
+    assert_eq!(config.unsafe_policy, None);
+    assert_eq!(config.effective_unsafe_policy(), UnsafePolicy::Fallback);
+
+    let cli = Cli::try_parse_from(["pwshfmt-rs", "check", "--unsafe-policy", "fallback"])
+        .expect("parse cli");
+    let config = config::load(&cli, workspace.path()).expect("load matching config");
+    assert_eq!(config.effective_unsafe_policy(), UnsafePolicy::Fallback);
+
+    let cli = Cli::try_parse_from(["pwshfmt-rs", "check", "--unsafe-policy", "format-anyway"])
+        .expect("parse cli");
+    let error = config::load(&cli, workspace.path()).expect_err("conflicting policies");
+    assert!(error.to_string().contains("format-anyway"));
+}
+
+#[test]
+fn explicit_partial_policy_is_not_overridden_by_legacy_strict_fallback() {
+    let workspace = common::create_workspace();
+    common::write_file(workspace.path(), "fallback.ps1", "# fallback");
+    common::write_file(
+        workspace.path(),
+        "pwshfmt-rs.toml",
+        "paths = [\"a.ps1\"]\nunsafe_policy = \"partial\"\nfallback_script = \"fallback.ps1\"\n",
+    );
+
+    let cli = Cli::try_parse_from(["pwshfmt-rs", "check"]).expect("parse cli");
+    let config = config::load(&cli, workspace.path()).expect("load explicit partial");
+    assert_eq!(config.unsafe_policy, Some(UnsafePolicy::Partial));
+    assert_eq!(config.effective_unsafe_policy(), UnsafePolicy::Partial);
+
+    common::write_file(
+        workspace.path(),
+        "pwshfmt-rs.toml",
+        "paths = [\"a.ps1\"]\nunsafe_policy = \"partial\"\nstrict_fallback = true\nfallback_script = \"fallback.ps1\"\n",
+    );
+    let error = config::load(&cli, workspace.path()).expect_err("conflicting policies");
+    assert!(error.to_string().contains("strict_fallback"));
+}
+
+#[test]
+fn rules_table_is_layered_from_file_env_and_cli() {
+    let workspace = common::create_workspace();
+    common::write_file(
+        workspace.path(),
+        "pwshfmt-rs.toml",
+        r#"
+paths = ["a.ps1"]
+
+[rules]
+keyword_case = { style = "upper" }
+parameter_case = { enabled = false }
+member_case = { enabled = false }
+"#,
+    );
+
+    let cli = Cli::try_parse_from([
+        "pwshfmt-rs",
+        "check",
+        "--enable-rule",
+        "member_case",
+        "--disable-rule",
+        "command_case",
+    ])
+    .expect("parse cli");
+
+    temp_env::with_vars(
+        [
+            ("PWSHFMT_RS_RULES__KEYWORD_CASE__STYLE", Some("pascal")),
+            ("PWSHFMT_RS_RULES__ALIAS_EXPANSION__PLATFORM", Some("true")),
+        ],
+        || {
+            let config = config::load(&cli, workspace.path()).expect("load config");
+            let rules = config.rules;
+
+            assert_eq!(rules.keyword_case.style, CaseStyle::Pascal);
+            assert!(!rules.parameter_case.enabled);
+            assert!(rules.member_case.enabled);
+            assert!(!rules.command_case.enabled);
+            assert!(rules.alias_expansion.platform);
+            assert!(!rules.alias_expansion.enabled);
+            assert!(!rules.layout.enabled);
+            assert!(rules.operator_case.enabled);
+        },
+    );
+}
+
+#[test]
+fn unknown_rules_are_rejected() {
+    let workspace = common::create_workspace();
+    common::write_file(
+        workspace.path(),
+        "pwshfmt-rs.toml",
+        "paths = [\"a.ps1\"]\n\n[rules]\nkeyword_cases = { style = \"upper\" }\n",
+    );
+
+    let cli = Cli::try_parse_from(["pwshfmt-rs", "check"]).expect("parse cli");
+    assert!(config::load(&cli, workspace.path()).is_err());
+}
+
+#[test]
+fn unsafe_allow_entries_are_read_from_file() {
+    let workspace = common::create_workspace();
+    common::write_file(
+        workspace.path(),
+        "pwshfmt-rs.toml",
+        r#"
+paths = ["a.ps1"]
+
+[[unsafe_allow]]
+paths = ["install.ps1"]
+kinds = ["invoke_expression", "iex_alias"]
+
+[[unsafe_allow]]
+paths = ["scripts/legacy/**"]
+"#,
+    );
+
+    let cli = Cli::try_parse_from(["pwshfmt-rs", "check"]).expect("parse cli");
+    let config = config::load(&cli, workspace.path()).expect("load config");
+    assert_eq!(
+        config.unsafe_allow,
+        vec![
+            UnsafeAllow {
+                paths: vec!["install.ps1".to_string()],
+                kinds: vec![UnsafeKind::InvokeExpression, UnsafeKind::IexAlias],
+            },
+            UnsafeAllow {
+                paths: vec!["scripts/legacy/**".to_string()],
+                kinds: Vec::new(),
+            },
+        ]
+    );
+}
+
 #[test]
 fn config_disables_strict_fallback_inside_internal_fallback_context() {
     let workspace = common::create_workspace();
@@ -130,6 +386,7 @@ fn config_disables_strict_fallback_inside_internal_fallback_context() {
         let config = config::load(&cli, workspace.path()).expect("load guarded config");
 
         assert!(!config.strict_fallback);
+        assert_eq!(config.effective_unsafe_policy(), UnsafePolicy::Partial);
         assert_eq!(
             config.fallback_script,
             std::path::PathBuf::from("fallback.ps1")
@@ -0,0 +1,154 @@
+mod common;
+
+use std::fs;
+
+use clap::Parser;
+use pwshfmt_rs::{
+    cli::{Cli, Commands},
+    dict,
+    formatter::dictionary::DictionaryFile,
+};
+
+fn run_dict(args: &[&str], cwd: &std::path::Path) -> i32 {
+    let cli = Cli::try_parse_from(args).expect("parse cli");
+    let Commands::Dict(command) = &cli.command else {
+        panic!("expected dict subcommand");
+    };
+    dict::run(command, &cli, cwd).expect("run dict command")
+}
+
+#[test]
+fn dict_import_writes_normalized_dictionary() {
+    let workspace = common::create_workspace();
+    common::write_file(
+        workspace.path(),
+        "dump.json",
+        r#"[{ "Name": "Invoke-Pester", "Parameters": { "CI": {}, "Path": {} } }]"#,
+    );
+
+    let exit_code = run_dict(
+        &[
+            "pwshfmt-rs",
+            "dict",
+            "import",
+            "dump.json",
+            "--output",
+            "pester.toml",
+        ],
+        workspace.path(),
+    );
+    assert_eq!(exit_code, 0);
+
+    let file = DictionaryFile::load(&workspace.path().join("pester.toml")).expect("load output");
+    assert_eq!(file.commands, vec!["Invoke-Pester"]);
+    assert_eq!(
+        file.command_parameters.get("Invoke-Pester"),
+        Some(&vec!["CI".to_string(), "Path".to_string()])
+    );
+}
+
+#[test]
+fn dict_import_reads_utf16_dump_from_windows_powershell() {
+    let workspace = common::create_workspace();
+    let dump = r#"[{ "Name": "Invoke-Pester", "Parameters": { "CI": {} } }]"#;
+    let bytes: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain(dump.encode_utf16().flat_map(u16::to_le_bytes))
+        .collect();
+    fs::write(workspace.path().join("dump.json"), bytes).expect("write dump");
+    let dictionary: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain(
+            "commands = [\"Invoke-Build\"]\n"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
+        )
+        .collect();
+    fs::write(workspace.path().join("build.toml"), dictionary).expect("write dictionary");
+
+    let exit_code = run_dict(
+        &[
+            "pwshfmt-rs",
+            "dict",
+            "import",
+            "dump.json",
+            "--output",
+            "pester.toml",
+        ],
+        workspace.path(),
+    );
+    assert_eq!(exit_code, 0);
+
+    let file = DictionaryFile::load(&workspace.path().join("pester.toml")).expect("load output");
+    assert_eq!(file.commands, vec!["Invoke-Pester"]);
+    let file = DictionaryFile::load(&workspace.path().join("build.toml")).expect("load utf16");
+    assert_eq!(file.commands, vec!["Invoke-Build"]);
+}
+
+#[test]
+fn dict_merge_combines_toml_and_json_inputs() {
+    let workspace = common::create_workspace();
+    common::write_file(
+        workspace.path(),
+        "a.toml",
+        "commands = [\"get-foo\"]\nparameters = [\"Force\"]\n",
+    );
+    common::write_file(
+        workspace.path(),
+        "b.json",
+        r#"{ "commands": ["Get-Foo", "Set-Bar"] }"#,
+    );
+
+    let exit_code = run_dict(
+        &[
+            "pwshfmt-rs",
+            "dict",
+            "merge",
+            "a.toml",
+            "b.json",
+            "-o",
+            "merged.json",
+        ],
+        workspace.path(),
+    );
+    assert_eq!(exit_code, 0);
+
+    let merged = fs::read_to_string(workspace.path().join("merged.json")).expect("read merged");
+    let file: DictionaryFile = serde_json::from_str(&merged).expect("parse merged json");
+    assert_eq!(file.commands, vec!["Get-Foo", "Set-Bar"]);
+    assert_eq!(file.parameters, vec!["Force"]);
+}
+
+#[test]
+fn dict_show_uses_configured_dictionaries_without_target_paths() {
+    let workspace = common::create_workspace();
+    common::write_file(
+        workspace.path(),
+        "pwshfmt-rs.toml",
+        "casing_dictionaries = [\"team.toml\"]\n",
+    );
+    common::write_file(
+        workspace.path(),
+        "team.toml",
+        "[command_parameters]\n\"Invoke-Pester\" = [\"CI\"]\n",
+    );
+
+    assert_eq!(
+        run_dict(
+            &["pwshfmt-rs", "dict", "show", "invoke-pester"],
+            workspace.path()
+        ),
+        0
+    );
+    assert_eq!(
+        run_dict(&["pwshfmt-rs", "dict", "show", "-ci"], workspace.path()),
+        0
+    );
+    assert_eq!(
+        run_dict(
+            &["pwshfmt-rs", "dict", "show", "Get-Missing"],
+            workspace.path()
+        ),
+        1
+    );
+}
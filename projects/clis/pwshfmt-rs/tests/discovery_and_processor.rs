@@ -5,9 +5,12 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use pwshfmt_rs::{
-    config::Config,
+    config::{
+        CaseStyle, Config, Encoding, IndentStyle, LineEnding, Rules, UnsafeAllow, UnsafePolicy,
+    },
     discovery,
-    error::Result,
+    error::{AppError, Result},
+    formatter::safety::UnsafeKind,
     processor::{self, FallbackRunner, PwshFallbackRunner},
     summary::RunMode,
 };
@@ -43,6 +46,18 @@ fn config_with_path(path: &str) -> Config {
         recurse: false,
         strict_fallback: false,
         fallback_script: PathBuf::from("scripts/pwsh/devops/Format-PowerShellCode.ps1"),
+        unsafe_policy: None,
+        unsafe_allow: Vec::new(),
+        casing_dictionaries: Vec::new(),
+        learn_workspace_functions: false,
+        indent_style: IndentStyle::Space,
+        indent_width: 4,
+        align_assignments: true,
+        line_ending: LineEnding::Preserve,
+        insert_final_newline: false,
+        encoding: Encoding::Preserve,
+        bom_for_non_ascii: false,
+        rules: Rules::default(),
     }
 }
 
@@ -97,6 +112,18 @@ fn discovers_git_changed_files() {
         recurse: false,
         strict_fallback: false,
         fallback_script: PathBuf::from("scripts/pwsh/devops/Format-PowerShellCode.ps1"),
+        unsafe_policy: None,
+        unsafe_allow: Vec::new(),
+        casing_dictionaries: Vec::new(),
+        learn_workspace_functions: false,
+        indent_style: IndentStyle::Space,
+        indent_width: 4,
+        align_assignments: true,
+        line_ending: LineEnding::Preserve,
+        insert_final_newline: false,
+        encoding: Encoding::Preserve,
+        bom_for_non_ascii: false,
+        rules: Rules::default(),
     };
 
     let files = discovery::discover_files(&config, workspace.path()).expect("discover git changed");
@@ -157,6 +184,18 @@ fn git_changed_discovery_skips_excluded_directories() {
         recurse: false,
         strict_fallback: false,
         fallback_script: PathBuf::from("scripts/pwsh/devops/Format-PowerShellCode.ps1"),
+        unsafe_policy: None,
+        unsafe_allow: Vec::new(),
+        casing_dictionaries: Vec::new(),
+        learn_workspace_functions: false,
+        indent_style: IndentStyle::Space,
+        indent_width: 4,
+        align_assignments: true,
+        line_ending: LineEnding::Preserve,
+        insert_final_newline: false,
+        encoding: Encoding::Preserve,
+        bom_for_non_ascii: false,
+        rules: Rules::default(),
     };
 
     let files =
@@ -180,7 +219,10 @@ fn processor_supports_check_write_and_noop() {
     let check_summary = processor::run(RunMode::Check, &config, workspace.path(), &NoopFallback)
         .expect("check run");
     assert_eq!(check_summary.needs_fix, 1);
-    assert_eq!(check_summary.exit_code(RunMode::Check), 2);
+    assert_eq!(
+        check_summary.exit_code(RunMode::Check, UnsafePolicy::Partial),
+        2
+    );
 
     let write_summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
         .expect("write run");
@@ -195,6 +237,178 @@ fn processor_supports_check_write_and_noop() {
     assert_eq!(noop_summary.unchanged, 1);
 }
 
+#[test]
+fn processor_applies_configured_casing_dictionaries() {
+    let workspace = common::create_workspace();
+    let file = common::write_file(
+        workspace.path(),
+        "demo.ps1",
+        "invoke-pester -ci\nget-azcontext -listavailable\n",
+    );
+    common::write_file(
+        workspace.path(),
+        "dicts/pester.toml",
+        "commands = [\"Invoke-Pester\"]\nparameters = [\"CI\"]\n",
+    );
+    common::write_file(
+        workspace.path(),
+        "dicts/az.json",
+        r#"{ "commands": ["Get-AzContext"], "parameters": ["ListAvailable"] }"#,
+    );
+
+    let mut config = config_with_path("demo.ps1");
+    config.casing_dictionaries = vec![
+        PathBuf::from("dicts/pester.toml"),
+        PathBuf::from("dicts/az.json"),
+    ];
+
+    let summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
+        .expect("write run");
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.fixes.command, 2);
+    assert_eq!(summary.fixes.parameter, 2);
+    assert_eq!(
+        fs::read_to_string(&file).expect("read output"),
+        "Invoke-Pester -CI\nGet-AzContext -ListAvailable\n"
+    );
+}
+
+#[test]
+fn processor_learns_workspace_functions_when_enabled() {
+    let workspace = common::create_workspace();
+    common::write_file(
+        workspace.path(),
+        "lib/utils.psm1",
+        "function Get-RepoRoot {\n    param([string]$StartPath)\n}\n",
+    );
+    let caller = common::write_file(
+        workspace.path(),
+        "lib/caller.ps1",
+        "get-reporoot -startpath $PSScriptRoot\n",
+    );
+
+    let mut config = config_with_path("lib");
+    let disabled = processor::run(RunMode::Check, &config, workspace.path(), &NoopFallback)
+        .expect("check without learning");
+    assert_eq!(disabled.needs_fix, 0);
+
+    config.learn_workspace_functions = true;
+    let summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
+        .expect("write with learning");
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.fixes.command, 0);
+    assert_eq!(summary.fixes.function, 1);
+    assert_eq!(summary.fixes.parameter, 1);
+    assert_eq!(
+        fs::read_to_string(&caller).expect("read caller"),
+        "Get-RepoRoot -StartPath $PSScriptRoot\n"
+    );
+}
+
+#[test]
+fn processor_normalizes_line_endings_and_reports_mixed_files() {
+    let workspace = common::create_workspace();
+    let file = common::write_file(
+        workspace.path(),
+        "mixed.ps1",
+        "Get-ChildItem\r\nGet-Item\nGet-Location",
+    );
+
+    let mut config = config_with_path("mixed.ps1");
+    let preserved = processor::run(RunMode::Check, &config, workspace.path(), &NoopFallback)
+        .expect("check with preserve");
+    assert_eq!(preserved.unchanged, 1);
+    assert_eq!(preserved.mixed_line_endings, 1);
+
+    config.line_ending = LineEnding::Crlf;
+    config.insert_final_newline = true;
+    let summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
+        .expect("write with crlf");
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.fixes.line_ending, 1);
+    assert_eq!(summary.fixes.final_newline, 1);
+    assert_eq!(
+        fs::read_to_string(&file).expect("read output"),
+        "Get-ChildItem\r\nGet-Item\r\nGet-Location\r\n"
+    );
+}
+
+#[test]
+fn processor_round_trips_utf16_files_and_honors_forced_encoding() {
+    let workspace = common::create_workspace();
+    let file = workspace.path().join("legacy.ps1");
+    let utf16 = |text: &str| -> Vec<u8> {
+        [0xFF, 0xFE]
+            .into_iter()
+            .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect()
+    };
+    fs::write(&file, utf16("get-childitem # 中文\r\n")).expect("write utf16 file");
+
+    let mut config = config_with_path("legacy.ps1");
+    let summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
+        .expect("write utf16");
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.fixes.encoding, 0);
+    assert_eq!(
+        fs::read(&file).expect("read utf16 output"),
+        utf16("Get-ChildItem # 中文\r\n")
+    );
+
+    config.encoding = Encoding::Utf8Bom;
+    let summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
+        .expect("convert to utf8 bom");
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.fixes.encoding, 1);
+    assert_eq!(
+        fs::read(&file).expect("read utf8 output"),
+        "\u{feff}Get-ChildItem # 中文\r\n".as_bytes()
+    );
+}
+
+#[test]
+fn processor_adds_bom_to_scripts_with_non_ascii_text() {
+    let workspace = common::create_workspace();
+    let script = common::write_file(
+        workspace.path(),
+        "zh.ps1",
+        "Get-ChildItem\nWrite-Host '中文'\n",
+    );
+    let ascii = common::write_file(workspace.path(), "en.ps1", "Get-ChildItem\n");
+    let notes = common::write_file(workspace.path(), "notes.txt", "中文\n");
+
+    let mut config = config_with_path(".");
+    config.recurse = true;
+    config.bom_for_non_ascii = true;
+
+    let check = processor::run(RunMode::Check, &config, workspace.path(), &NoopFallback)
+        .expect("check bom");
+    assert_eq!(check.needs_fix, 1);
+    assert_eq!(check.fixes.bom, 1);
+
+    processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback).expect("write bom");
+    assert_eq!(
+        fs::read(&script).expect("read script"),
+        "\u{feff}Get-ChildItem\nWrite-Host '中文'\n".as_bytes()
+    );
+    assert_eq!(fs::read(&ascii).expect("read ascii"), b"Get-ChildItem\n");
+    assert_eq!(fs::read(&notes).expect("read notes"), "中文\n".as_bytes());
+}
+
+#[test]
+fn processor_reports_invalid_casing_dictionary() {
+    let workspace = common::create_workspace();
+    common::write_file(workspace.path(), "demo.ps1", "get-childitem\n");
+    common::write_file(workspace.path(), "broken.toml", "commands = [\n");
+
+    let mut config = config_with_path("demo.ps1");
+    config.casing_dictionaries = vec![PathBuf::from("broken.toml")];
+
+    let error = processor::run(RunMode::Check, &config, workspace.path(), &NoopFallback)
+        .expect_err("broken dictionary should fail");
+    assert!(matches!(error, AppError::DictionaryParse { .. }));
+}
+
 #[test]
 fn processor_uses_strict_fallback_on_unsafe_tokens() {
     let workspace = common::create_workspace();
@@ -223,6 +437,140 @@ fn processor_uses_strict_fallback_on_unsafe_tokens() {
     assert!(content.contains("Invoke-Expression"));
 }
 
+#[test]
+fn processor_formats_safe_parts_of_files_with_dynamic_code() {
+    let workspace = common::create_workspace();
+    let file = common::write_file(
+        workspace.path(),
+        "partial.ps1",
+        "get-childitem -path .\ninvoke-expression $command\nwrite-host 'done'\n",
+    );
+
+    let config = config_with_path("partial.ps1");
+    let summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
+        .expect("write run");
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.partial, 1);
+    assert_eq!(summary.fallback_invoked, 0);
+    assert_eq!(
+        summary.unsafe_findings.get(&UnsafeKind::InvokeExpression),
+        Some(&1)
+    );
+
+    let content = fs::read_to_string(file).expect("read file");
+    assert_eq!(
+        content,
+        "Get-ChildItem -Path .\ninvoke-expression $command\nWrite-Host 'done'\n"
+    );
+}
+
+#[test]
+fn processor_leaves_suppressed_files_and_regions_untouched() {
+    let workspace = common::create_workspace();
+    let skipped_input = "# pwshfmt: skip\r\nget-childitem -path .";
+    let skipped = common::write_file(workspace.path(), "skipped.ps1", skipped_input);
+    let partial = common::write_file(
+        workspace.path(),
+        "partial.ps1",
+        "# pwshfmt-ignore-next-line\nget-childitem -path .\nget-childitem -path .\n",
+    );
+
+    let mut config = config_with_path(".");
+    config.line_ending = LineEnding::Lf;
+    config.insert_final_newline = true;
+
+    let summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
+        .expect("write run");
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.suppressed, 2);
+    assert_eq!(
+        fs::read_to_string(skipped).expect("read file"),
+        skipped_input
+    );
+    assert_eq!(
+        fs::read_to_string(partial).expect("read file"),
+        "# pwshfmt-ignore-next-line\nget-childitem -path .\nGet-ChildItem -Path .\n"
+    );
+}
+
+#[test]
+fn processor_applies_rules_table() {
+    let workspace = common::create_workspace();
+    let file = common::write_file(
+        workspace.path(),
+        "rules.ps1",
+        "if ($a)\n{\nget-childitem -path .\n}\n",
+    );
+
+    let mut config = config_with_path("rules.ps1");
+    config.rules.command_case.enabled = false;
+    config.rules.keyword_case.style = CaseStyle::Upper;
+    config.rules.layout.enabled = true;
+
+    processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback).expect("write run");
+    assert_eq!(
+        fs::read_to_string(file).expect("read file"),
+        "IF ($a) {\n    get-childitem -Path .\n}\n"
+    );
+}
+
+#[test]
+fn processor_skips_allowlisted_dynamic_code() {
+    let workspace = common::create_workspace();
+    let input = "get-childitem -path .\ninvoke-expression $bootstrap\n& $other\n";
+    let file = common::write_file(workspace.path(), "install.ps1", input);
+    common::write_file(workspace.path(), "other/install.ps1", input);
+
+    let mut config = config_with_path(".");
+    config.recurse = true;
+    config.unsafe_policy = Some(UnsafePolicy::Skip);
+    config.unsafe_allow = vec![UnsafeAllow {
+        paths: vec!["install.ps1".to_string()],
+        kinds: vec![UnsafeKind::InvokeExpression, UnsafeKind::DynamicCall],
+    }];
+
+    let summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
+        .expect("write run");
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.skipped, 1);
+    assert_eq!(
+        fs::read_to_string(file).expect("read file"),
+        "Get-ChildItem -Path .\nInvoke-Expression $bootstrap\n& $other\n"
+    );
+}
+
+#[test]
+fn processor_applies_unsafe_policy() {
+    let workspace = common::create_workspace();
+    let input = "get-childitem -path .\ninvoke-expression $command\n";
+    let file = common::write_file(workspace.path(), "policy.ps1", input);
+    let mut config = config_with_path("policy.ps1");
+
+    config.unsafe_policy = Some(UnsafePolicy::Skip);
+    let summary =
+        processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback).expect("skip run");
+    assert_eq!(summary.skipped, 1);
+    assert_eq!(summary.exit_code(RunMode::Write, UnsafePolicy::Skip), 0);
+    assert_eq!(fs::read_to_string(&file).expect("read file"), input);
+
+    config.unsafe_policy = Some(UnsafePolicy::Fail);
+    let summary =
+        processor::run(RunMode::Check, &config, workspace.path(), &NoopFallback).expect("fail run");
+    assert_eq!(summary.skipped, 1);
+    assert_eq!(summary.needs_fix, 0);
+    assert_eq!(summary.exit_code(RunMode::Check, UnsafePolicy::Fail), 3);
+
+    config.unsafe_policy = Some(UnsafePolicy::FormatAnyway);
+    let summary = processor::run(RunMode::Write, &config, workspace.path(), &NoopFallback)
+        .expect("format-anyway run");
+    assert_eq!(summary.updated, 1);
+    assert_eq!(summary.partial, 0);
+    assert_eq!(
+        fs::read_to_string(&file).expect("read file"),
+        "Get-ChildItem -Path .\nInvoke-Expression $command\n"
+    );
+}
+
 #[test]
 fn pwsh_fallback_runner_marks_internal_fallback_context() {
     if !pwsh_is_available() {